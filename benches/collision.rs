@@ -0,0 +1,87 @@
+//! Measures the simulation tick time spent on collision detection with each `Broadphase`.
+//!
+//! Run it with `cargo bench --bench collision`. Colliders of every layer are scattered over the
+//! play field and drift slowly, so contacts keep starting and ending.
+
+use std::time::{Duration, Instant};
+
+use bevy::{math::vec2, prelude::*, time::TimeUpdateStrategy};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rusty_space_invaders::{
+    collision::{broadphase::Broadphase, CollisionLayer, CollisionPlugin, CollisionShape},
+    default_config::{WINDOW_X_SIZE, WINDOW_Y_SIZE},
+    headless::run_headless,
+    prelude::Collider,
+    simulation::{SimulationPlugin, SIMULATION_TIMESTEP},
+};
+
+const ENTITY_COUNTS: [usize; 2] = [1_000, 10_000];
+const WARM_UP_TICKS: u32 = 5;
+const MEASURED_TICKS: u32 = 20;
+const LAYERS: [CollisionLayer; 5] = [
+    CollisionLayer::Player,
+    CollisionLayer::PlayerProjectile,
+    CollisionLayer::Enemy,
+    CollisionLayer::EnemyProjectile,
+    CollisionLayer::Pickup,
+];
+
+/// Velocity of a benchmark collider, in pixels per second.
+#[derive(Component)]
+struct Drift(Vec2);
+
+fn main() {
+    for entities in ENTITY_COUNTS {
+        for broadphase in [Broadphase::BruteForce, Broadphase::SpatialGrid] {
+            let tick_time = measure_tick_time(entities, broadphase);
+
+            println!(
+                "{entities:>6} entities, {broadphase:?}: {:.3} ms per tick",
+                tick_time.as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
+
+fn measure_tick_time(entities: usize, broadphase: Broadphase) -> Duration {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SimulationPlugin, CollisionPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(SIMULATION_TIMESTEP))
+        .insert_resource(broadphase)
+        .add_systems(FixedUpdate, drift_system);
+
+    // Same colliders for every broadphase
+    let mut rng = StdRng::seed_from_u64(0);
+    let half_field = vec2(WINDOW_X_SIZE, WINDOW_Y_SIZE) / 2.0;
+
+    for index in 0..entities {
+        let position = vec2(
+            rng.gen_range(-half_field.x..half_field.x),
+            rng.gen_range(-half_field.y..half_field.y),
+        );
+        let size = vec2(rng.gen_range(4.0..24.0), rng.gen_range(4.0..24.0));
+        let velocity = vec2(rng.gen_range(-60.0..60.0), rng.gen_range(-60.0..60.0));
+
+        app.world_mut().spawn((
+            Collider::new(
+                CollisionShape::rectangle(size),
+                LAYERS[index % LAYERS.len()],
+            ),
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            Drift(velocity),
+        ));
+    }
+
+    run_headless(&mut app, WARM_UP_TICKS);
+
+    let start = Instant::now();
+    run_headless(&mut app, MEASURED_TICKS);
+
+    start.elapsed() / MEASURED_TICKS
+}
+
+fn drift_system(time: Res<Time>, mut query: Query<(&mut Transform, &Drift)>) {
+    for (mut transform, drift) in &mut query {
+        transform.translation += (drift.0 * time.delta_seconds()).extend(0.0);
+    }
+}
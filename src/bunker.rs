@@ -0,0 +1,206 @@
+use bevy::{math::vec2, prelude::*};
+
+use crate::{
+    collision::{CollisionLayer, CollisionShape, CollisionStartedEvent},
+    default_config::WINDOW_X_LIMIT,
+    enemy::{
+        enemy_take_damage_listener,
+        wave::{Level, LevelHandle, WaveStartedEvent},
+        Enemy,
+    },
+    game_state::{GameState, InGame, NewGameSet},
+    prelude::{Collider, Damage, Health},
+    tuning::BunkerTuning,
+};
+
+const BUNKER_COLOR: Color = Color::srgb(0.3, 0.9, 0.4);
+/// Opacity of a cell about to be destroyed, fully opaque cells being untouched.
+const WORN_CELL_ALPHA: f32 = 0.3;
+
+/// Builds the bunkers between the ship and the enemies.
+///
+/// Bunkers are made of small `BunkerCell`s worn down one by one. They are built for every new
+/// run, when a wave asks for it and when their tuning changes.
+pub struct BunkerPlugin;
+
+impl Plugin for BunkerPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_systems(PreUpdate, build_bunkers_system.in_set(NewGameSet))
+            .add_systems(
+                FixedUpdate,
+                (
+                    rebuild_bunkers_listener,
+                    // Shots which hit an enemy on the same tick are already gone
+                    bunker_take_damage_listener.after(enemy_take_damage_listener),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                destroy_bunker_cells_system.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                build_bunkers_system
+                    .run_if(in_state(InGame).and_then(resource_changed::<BunkerTuning>)),
+            );
+    }
+}
+
+/// Tags a piece of a bunker.
+#[derive(Component)]
+pub struct BunkerCell;
+
+#[derive(Bundle)]
+struct BunkerCellBundle {
+    sprite: SpriteBundle,
+    cell: BunkerCell,
+    health: Health,
+    collider: Collider,
+}
+
+impl BunkerCellBundle {
+    fn new(position: Vec2, tuning: &BunkerTuning) -> Self {
+        let size = Vec2::splat(tuning.cell_size);
+
+        Self {
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: BUNKER_COLOR,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..Default::default()
+            },
+            cell: BunkerCell,
+            health: Health(tuning.cell_health),
+            collider: Collider::new(CollisionShape::rectangle(size), CollisionLayer::Barrier),
+        }
+    }
+}
+
+/// Whether the cell at `row` and `column`, counted from the bottom left corner, is part of
+/// the arch cut out of the bottom third of the bunkers.
+fn is_arch_cell(row: u32, column: u32, tuning: &BunkerTuning) -> bool {
+    let arch_margin = tuning.columns / 3;
+
+    row < tuning.rows / 3 && column >= arch_margin && column < tuning.columns - arch_margin
+}
+
+/// Replaces every bunker cell with brand new bunkers evenly spread across the screen.
+fn build_bunkers(
+    commands: &mut Commands,
+    cells_query: &Query<Entity, With<BunkerCell>>,
+    tuning: &BunkerTuning,
+) {
+    for cell_entity in cells_query {
+        commands.entity(cell_entity).despawn();
+    }
+
+    let slot_width = 2.0 * WINDOW_X_LIMIT / tuning.count as f32;
+    let bunker_size = vec2(tuning.columns as f32, tuning.rows as f32) * tuning.cell_size;
+
+    for bunker in 0..tuning.count {
+        let center = vec2(
+            -WINDOW_X_LIMIT + slot_width * (bunker as f32 + 0.5),
+            tuning.y_position,
+        );
+        let bottom_left = center - bunker_size / 2.0;
+
+        for row in 0..tuning.rows {
+            for column in 0..tuning.columns {
+                if is_arch_cell(row, column, tuning) {
+                    continue;
+                }
+
+                let position =
+                    bottom_left + (vec2(column as f32, row as f32) + 0.5) * tuning.cell_size;
+
+                commands.spawn((BunkerCellBundle::new(position, tuning), StateScoped(InGame)));
+            }
+        }
+    }
+}
+
+fn build_bunkers_system(
+    tuning: Res<BunkerTuning>,
+    cells_query: Query<Entity, With<BunkerCell>>,
+    mut commands: Commands,
+) {
+    build_bunkers(&mut commands, &cells_query, &tuning);
+}
+
+/// Builds the bunkers again when a wave which asks for it starts.
+fn rebuild_bunkers_listener(
+    mut wave_started_event_rx: EventReader<WaveStartedEvent>,
+    levels: Res<Assets<Level>>,
+    level_handle: Res<LevelHandle>,
+    tuning: Res<BunkerTuning>,
+    cells_query: Query<Entity, With<BunkerCell>>,
+    mut commands: Commands,
+) {
+    let Some(level) = levels.get(&level_handle.0) else {
+        return;
+    };
+
+    let rebuild = wave_started_event_rx
+        .read()
+        .any(|WaveStartedEvent(wave_number)| level.wave(*wave_number).rebuild_bunkers);
+
+    if rebuild {
+        build_bunkers(&mut commands, &cells_query, &tuning);
+    }
+}
+
+/// Listens for collisions with bunker cells and wears the cells down, fading them as they
+/// lose health.
+///
+/// Shots and enemy projectiles damage the first cell they touch and are consumed by the hit,
+/// while enemies wipe out every cell they go through.
+fn bunker_take_damage_listener(
+    mut collision_started_event_rx: EventReader<CollisionStartedEvent>,
+    projectiles_query: Query<&Damage>,
+    enemies_query: Query<(), With<Enemy>>,
+    mut cells_query: Query<(&mut Health, &mut Sprite), With<BunkerCell>>,
+    tuning: Res<BunkerTuning>,
+    mut commands: Commands,
+) {
+    // Projectiles despawned by an earlier hit of the same tick
+    let mut spent_projectiles = Vec::new();
+
+    for CollisionStartedEvent(hitter_entity, cell_entity) in collision_started_event_rx.read() {
+        let Ok((mut cell_health, mut cell_sprite)) = cells_query.get_mut(*cell_entity) else {
+            continue;
+        };
+
+        if enemies_query.contains(*hitter_entity) {
+            cell_health.0 = 0.0;
+        } else if let Ok(damage) = projectiles_query.get(*hitter_entity) {
+            if spent_projectiles.contains(hitter_entity) {
+                continue;
+            }
+
+            cell_health.0 -= damage.0;
+            commands.entity(*hitter_entity).despawn();
+            spent_projectiles.push(*hitter_entity);
+        }
+
+        let health_left = (cell_health.0 / tuning.cell_health).clamp(0.0, 1.0);
+        cell_sprite
+            .color
+            .set_alpha(WORN_CELL_ALPHA + (1.0 - WORN_CELL_ALPHA) * health_left);
+    }
+}
+
+/// Despawns the bunker cells without health left.
+fn destroy_bunker_cells_system(
+    cells_query: Query<(Entity, &Health), With<BunkerCell>>,
+    mut commands: Commands,
+) {
+    for (cell_entity, cell_health) in &cells_query {
+        if cell_health.0 <= 0.0 {
+            commands.entity(cell_entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::{
+    math::{bounding::Aabb2d, vec2, UVec2, Vec2},
+    prelude::Resource,
+};
+
+use crate::default_config::{WINDOW_X_SIZE, WINDOW_Y_SIZE};
+
+/// Side of the square cells of the default `SpatialGrid`, close to the size of the largest
+/// colliders so most of them only cover a few cells.
+pub const GRID_CELL_SIZE: f32 = 50.0;
+
+/// How the collision detection finds the pairs of colliders worth checking.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Broadphase {
+    /// Checks every pair of colliders, which is only affordable with few entities.
+    BruteForce,
+    /// Only checks colliders sharing a cell of a `SpatialGrid`.
+    #[default]
+    SpatialGrid,
+}
+
+/// Uniform grid covering the play field, which buckets colliders by the cells their bounds
+/// cover.
+///
+/// Colliders partly or fully outside the play field are kept in the border cells, so the
+/// grid never grows.
+pub struct SpatialGrid {
+    cell_size: f32,
+    /// Bottom left corner of the grid.
+    origin: Vec2,
+    size: UVec2,
+    /// Indices of the colliders covering each cell, row by row.
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    /// Creates a grid over the play field made of cells of `cell_size`.
+    pub fn new(cell_size: f32) -> Self {
+        let field_size = vec2(WINDOW_X_SIZE, WINDOW_Y_SIZE);
+        let size = (field_size / cell_size).ceil().as_uvec2().max(UVec2::ONE);
+
+        Self {
+            cell_size,
+            origin: -field_size / 2.0,
+            size,
+            cells: vec![Vec::new(); (size.x * size.y) as usize],
+        }
+    }
+
+    /// Removes every collider from the grid, keeping the allocated cells.
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+    }
+
+    /// Adds the collider at `index` to every cell covered by its `bounds`.
+    pub fn insert(&mut self, index: usize, bounds: &Aabb2d) {
+        let min = self.cell(bounds.min);
+        let max = self.cell(bounds.max);
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = self.cell_index(UVec2::new(x, y));
+                self.cells[cell].push(index);
+            }
+        }
+    }
+
+    /// Calls `check` with the indices of every pair of colliders sharing a cell.
+    ///
+    /// `bounds` holds the bounds of the inserted colliders by index. Pairs of overlapping
+    /// colliders are only given once, from the cell holding the corner where their overlap
+    /// starts, while pairs which do not overlap may be given several times.
+    pub fn for_each_candidate_pair(&self, bounds: &[Aabb2d], mut check: impl FnMut(usize, usize)) {
+        for (cell, indices) in self.cells.iter().enumerate() {
+            for (position, &index) in indices.iter().enumerate() {
+                for &other_index in &indices[position + 1..] {
+                    let overlap_start = bounds[index].min.max(bounds[other_index].min);
+
+                    if self.cell_index(self.cell(overlap_start)) == cell {
+                        check(index, other_index);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets the cell holding `point`, or the closest border cell for points out of the grid.
+    fn cell(&self, point: Vec2) -> UVec2 {
+        let cell = ((point - self.origin) / self.cell_size).floor();
+
+        cell.max(Vec2::ZERO).as_uvec2().min(self.size - 1)
+    }
+
+    fn cell_index(&self, cell: UVec2) -> usize {
+        (cell.y * self.size.x + cell.x) as usize
+    }
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid::new(GRID_CELL_SIZE)
+    }
+}
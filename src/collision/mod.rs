@@ -0,0 +1,307 @@
+use std::{collections::HashSet, ops::BitOr};
+
+use bevy::{
+    math::bounding::{Aabb2d, Bounded2d, IntersectsVolume},
+    prelude::*,
+    transform::systems::{propagate_transforms, sync_simple_transforms},
+};
+use broadphase::{Broadphase, SpatialGrid};
+
+pub mod broadphase;
+
+/// Finds which colliders touch each other and reports it through collision events.
+///
+/// Bounds are refreshed from the entity `GlobalTransform` once at the start of every simulation
+/// tick, so all the collisions of a tick come from the same snapshot and are known before
+/// gameplay runs. Movement systems only have to update `Transform`, and interactions between
+/// two kinds of entities are handled by listening for `CollisionStartedEvent`. Only the pairs
+/// of colliders given by the `Broadphase` resource are checked, which is a `SpatialGrid` unless
+/// replaced.
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Contacts>()
+            .init_resource::<Broadphase>()
+            .add_event::<CollisionStartedEvent>()
+            .add_event::<CollisionEndedEvent>()
+            .add_systems(
+                FixedPreUpdate,
+                (
+                    // `GlobalTransform` is otherwise only updated before rendering, from the
+                    // interpolated transforms
+                    sync_simple_transforms,
+                    propagate_transforms,
+                    update_collider_bounds_system,
+                    detect_collisions_system,
+                )
+                    .chain()
+                    .in_set(CollisionSet),
+            );
+    }
+}
+
+/// Systems which update the colliders and send the collision events of the tick.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CollisionSet;
+
+/// Groups of colliders, used to pick which colliders can touch each other.
+///
+/// Layers are ordered, and collision events always list the entity with the lowest layer first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CollisionLayer {
+    Player,
+    PlayerProjectile,
+    Enemy,
+    EnemyProjectile,
+    Pickup,
+    Barrier,
+}
+
+impl CollisionLayer {
+    /// Layers which this one collides with unless told otherwise.
+    pub fn default_mask(&self) -> LayerMask {
+        match self {
+            CollisionLayer::Player => {
+                CollisionLayer::Enemy | CollisionLayer::EnemyProjectile | CollisionLayer::Pickup
+            }
+            CollisionLayer::PlayerProjectile => CollisionLayer::Enemy | CollisionLayer::Barrier,
+            CollisionLayer::Enemy => {
+                CollisionLayer::Player | CollisionLayer::PlayerProjectile | CollisionLayer::Barrier
+            }
+            CollisionLayer::EnemyProjectile => CollisionLayer::Player | CollisionLayer::Barrier,
+            CollisionLayer::Pickup => CollisionLayer::Player.into(),
+            CollisionLayer::Barrier => {
+                CollisionLayer::PlayerProjectile
+                    | CollisionLayer::Enemy
+                    | CollisionLayer::EnemyProjectile
+            }
+        }
+    }
+}
+
+/// Set of `CollisionLayer`, built by combining layers with `|`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LayerMask(u8);
+
+impl LayerMask {
+    pub const NONE: LayerMask = LayerMask(0);
+
+    pub fn contains(&self, layer: CollisionLayer) -> bool {
+        self.0 & LayerMask::from(layer).0 != 0
+    }
+}
+
+impl From<CollisionLayer> for LayerMask {
+    fn from(layer: CollisionLayer) -> Self {
+        LayerMask(1 << layer as u8)
+    }
+}
+
+impl BitOr for LayerMask {
+    type Output = LayerMask;
+
+    fn bitor(self, rhs: LayerMask) -> LayerMask {
+        LayerMask(self.0 | rhs.0)
+    }
+}
+
+impl BitOr for CollisionLayer {
+    type Output = LayerMask;
+
+    fn bitor(self, rhs: CollisionLayer) -> LayerMask {
+        LayerMask::from(self) | LayerMask::from(rhs)
+    }
+}
+
+impl BitOr<CollisionLayer> for LayerMask {
+    type Output = LayerMask;
+
+    fn bitor(self, rhs: CollisionLayer) -> LayerMask {
+        self | LayerMask::from(rhs)
+    }
+}
+
+/// Shape of a `Collider` in the local space of its entity, centered on its origin.
+#[derive(Clone, Copy, Debug)]
+pub enum CollisionShape {
+    Rectangle(Rectangle),
+    Circle(Circle),
+    /// Capsule standing along the local y axis.
+    Capsule(Capsule2d),
+}
+
+impl CollisionShape {
+    pub fn rectangle(size: Vec2) -> Self {
+        CollisionShape::Rectangle(Rectangle::from_size(size))
+    }
+
+    pub fn circle(radius: f32) -> Self {
+        CollisionShape::Circle(Circle::new(radius))
+    }
+
+    /// Creates the largest vertical capsule fitting in `size`.
+    pub fn capsule(size: Vec2) -> Self {
+        CollisionShape::Capsule(Capsule2d::new(size.x / 2.0, (size.y - size.x).max(0.0)))
+    }
+
+    /// Computes the axis-aligned bounds of the shape once scaled, rotated and moved by
+    /// `transform`.
+    pub fn world_bounds(&self, transform: &GlobalTransform) -> Aabb2d {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let scale = scale.truncate().abs();
+        let translation = translation.truncate();
+        let rotation = Rot2::radians(rotation.to_euler(EulerRot::ZYX).0);
+
+        match *self {
+            CollisionShape::Rectangle(rectangle) => Rectangle {
+                half_size: rectangle.half_size * scale,
+            }
+            .aabb_2d(translation, rotation),
+            // Circles stay round, so they grow with the largest scale
+            CollisionShape::Circle(circle) => {
+                Circle::new(circle.radius * scale.max_element()).aabb_2d(translation, rotation)
+            }
+            CollisionShape::Capsule(capsule) => Capsule2d {
+                radius: capsule.radius * scale.x,
+                half_length: capsule.half_length * scale.y,
+            }
+            .aabb_2d(translation, rotation),
+        }
+    }
+}
+
+/// Provides `Collider` attribute to an entity.
+///
+/// It holds the local shape of the entity, its layer and its world bounds for the current tick,
+/// which are used for collision management. Two colliders only collide when the mask of each
+/// one contains the layer of the other.
+#[derive(Component, Debug)]
+pub struct Collider {
+    pub shape: CollisionShape,
+    pub layer: CollisionLayer,
+    pub mask: LayerMask,
+    bounds: Option<Aabb2d>,
+}
+
+impl Collider {
+    /// Creates a collider in `layer`, colliding with the layers of its default mask.
+    pub fn new(shape: CollisionShape, layer: CollisionLayer) -> Self {
+        Self {
+            shape,
+            layer,
+            mask: layer.default_mask(),
+            bounds: None,
+        }
+    }
+
+    pub fn with_mask(mut self, mask: LayerMask) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Whether the layers of both colliders let them collide.
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        self.mask.contains(other.layer) && other.mask.contains(self.layer)
+    }
+
+    /// World bounds of the collider, unknown until the next tick for just spawned entities.
+    pub fn bounds(&self) -> Option<Aabb2d> {
+        self.bounds
+    }
+
+    /// Whether the world bounds of both colliders overlap.
+    pub fn intersects(&self, other: &Collider) -> bool {
+        match (self.bounds, other.bounds) {
+            (Some(bounds), Some(other_bounds)) => bounds.intersects(&other_bounds),
+            _ => false,
+        }
+    }
+}
+
+/// Recomputes the world bounds of every collider from its shape and `GlobalTransform`.
+fn update_collider_bounds_system(mut query: Query<(&mut Collider, &GlobalTransform)>) {
+    for (mut collider, transform) in &mut query {
+        collider.bounds = Some(collider.shape.world_bounds(transform));
+    }
+}
+
+/// Sent on the first tick two colliders touch each other.
+///
+/// The entity with the lowest `CollisionLayer` comes first, so a shot always comes before the
+/// enemy it hits.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionStartedEvent(pub Entity, pub Entity);
+
+/// Sent on the first tick two colliders stop touching each other, including when one of them is
+/// despawned. Entities are ordered like in `CollisionStartedEvent`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionEndedEvent(pub Entity, pub Entity);
+
+/// Pairs of colliders touching each other on the last tick, ordered like in collision events.
+#[derive(Resource, Default)]
+struct Contacts(HashSet<(Entity, Entity)>);
+
+/// Checks the pairs of colliders given by the `Broadphase` whose layers interact, and sends
+/// an event for each contact which started or ended since the last tick.
+fn detect_collisions_system(
+    query: Query<(Entity, &Collider)>,
+    broadphase: Res<Broadphase>,
+    mut grid: Local<SpatialGrid>,
+    mut contacts: ResMut<Contacts>,
+    mut collision_started_event_tx: EventWriter<CollisionStartedEvent>,
+    mut collision_ended_event_tx: EventWriter<CollisionEndedEvent>,
+) {
+    let (colliders, bounds): (Vec<_>, Vec<_>) = query
+        .iter()
+        .filter_map(|(entity, collider)| Some(((entity, collider), collider.bounds?)))
+        .unzip();
+    let mut current_contacts = HashSet::new();
+    let mut check = |index: usize, other_index: usize| {
+        let (entity, collider) = colliders[index];
+        let (other_entity, other_collider) = colliders[other_index];
+
+        if !collider.interacts_with(other_collider) || !collider.intersects(other_collider) {
+            return;
+        }
+
+        let pair = if (collider.layer, entity) <= (other_collider.layer, other_entity) {
+            (entity, other_entity)
+        } else {
+            (other_entity, entity)
+        };
+        current_contacts.insert(pair);
+    };
+
+    match *broadphase {
+        Broadphase::BruteForce => {
+            for index in 0..colliders.len() {
+                for other_index in index + 1..colliders.len() {
+                    check(index, other_index);
+                }
+            }
+        }
+        Broadphase::SpatialGrid => {
+            grid.clear();
+            for (index, collider_bounds) in bounds.iter().enumerate() {
+                grid.insert(index, collider_bounds);
+            }
+            grid.for_each_candidate_pair(&bounds, check);
+        }
+    }
+
+    // Sorted so events come in the same order on every run
+    let mut started: Vec<_> = current_contacts.difference(&contacts.0).copied().collect();
+    let mut ended: Vec<_> = contacts.0.difference(&current_contacts).copied().collect();
+    started.sort();
+    ended.sort();
+
+    for (entity, other_entity) in started {
+        collision_started_event_tx.send(CollisionStartedEvent(entity, other_entity));
+    }
+    for (entity, other_entity) in ended {
+        collision_ended_event_tx.send(CollisionEndedEvent(entity, other_entity));
+    }
+
+    contacts.0 = current_contacts;
+}
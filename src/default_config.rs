@@ -10,6 +10,10 @@ const WINDOW_X_PADDING: f32 = 60.0;
 pub const WINDOW_X_LIMIT: f32 = (WINDOW_X_SIZE / 2.0) - WINDOW_X_PADDING;
 pub const WINDOW_Y_LIMIT: f32 = WINDOW_Y_SIZE / 2.0;
 pub const WINDOW_Y_PADDING: f32 = 30.0;
+/// Distance between the side edges of the screen and the HUD.
+pub const HUD_X_PADDING: f32 = 30.0;
+/// Horizontal distance between the center of the screen and the HUD side edges.
+pub const HUD_X_LIMIT: f32 = WINDOW_X_SIZE / 2.0 - HUD_X_PADDING;
 
 pub struct DefaultConfigPlugins;
 
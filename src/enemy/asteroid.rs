@@ -1,23 +1,20 @@
 use bevy::{
-    math::{
-        bounding::{Aabb2d, BoundingVolume},
-        vec2, vec3,
-    },
+    math::{vec2, vec3},
     prelude::*,
 };
 use rand::Rng;
+use serde::Deserialize;
 
 use crate::{
-    default_config::{WINDOW_X_LIMIT, WINDOW_Y_LIMIT},
-    prelude::{Collider, Health, YSpeed},
+    collision::{CollisionLayer, CollisionShape},
+    game_state::InGame,
+    play_area::Bounded,
+    prelude::{Collider, Health, XSpeed, YSpeed},
+    simulation::Interpolated,
+    tuning::AsteroidTuning,
 };
 
-use super::{Enemy, EnemyKind};
-
-const SPAWN_Y_OFFSET: f32 = 45.0;
-
-#[derive(Resource)]
-pub struct AsteroidSpawnTimer(pub Timer);
+use super::{Enemy, EnemyDiedEvent, EnemyKind};
 
 #[derive(Component)]
 pub struct Asteroid;
@@ -25,76 +22,181 @@ pub struct Asteroid;
 #[derive(Bundle)]
 struct AsteroidBundle {
     sprite: SpriteBundle,
-    speed: YSpeed,
+    x_speed: XSpeed,
+    y_speed: YSpeed,
     health: Health,
     asteroid: Asteroid,
     collider: Collider,
     enemy: Enemy,
+    interpolated: Interpolated,
+    bounded: Bounded,
 }
 
-pub const ASTEROID_SCORE: u32 = 1;
-pub const ASTEROID_SPAWNER_TRIGGER_INTERVAL: f32 = 2.0;
+pub const BIG_ASTEROID_SCORE: u32 = 20;
+pub const MEDIUM_ASTEROID_SCORE: u32 = 50;
+pub const SMALL_ASTEROID_SCORE: u32 = 100;
 pub const ASTEROID_ROTATION_SPEED: f32 = 1.25;
-pub const ASTEROID_SPEED: f32 = 2.0;
-pub const ASTEROID_HEALTH: f32 = 5.0;
-pub const ASTEROID_COLLIDER_SIZE: (f32, f32) = (101.0, 84.0); // hardcoded size because we should have the boundaries of the collider and it should not be given by the sprite
+/// Amount of fragments a destroyed asteroid splits into, both ends included.
+const ASTEROID_FRAGMENTS: (u32, u32) = (2, 3);
+/// Asteroids spawn above the screen, so they are only despawned once this far away from it.
+const ASTEROID_AREA_MARGIN: f32 = 100.0;
+
+/// Size tier of an asteroid. Bigger asteroids split into smaller ones when destroyed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum AsteroidSize {
+    Big,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    /// Gets the score given by destroying an asteroid of this size.
+    pub fn score(&self) -> u32 {
+        match self {
+            AsteroidSize::Big => BIG_ASTEROID_SCORE,
+            AsteroidSize::Medium => MEDIUM_ASTEROID_SCORE,
+            AsteroidSize::Small => SMALL_ASTEROID_SCORE,
+        }
+    }
+
+    /// Gets the size of the fragments this asteroid splits into, if any.
+    pub fn fragment(&self) -> Option<AsteroidSize> {
+        match self {
+            AsteroidSize::Big => Some(AsteroidSize::Medium),
+            AsteroidSize::Medium => Some(AsteroidSize::Small),
+            AsteroidSize::Small => None,
+        }
+    }
+
+    /// Scale applied to the sprite, which is drawn for the big asteroid.
+    fn sprite_scale(&self) -> f32 {
+        match self {
+            AsteroidSize::Big => 1.0,
+            AsteroidSize::Medium => 0.6,
+            AsteroidSize::Small => 0.35,
+        }
+    }
+}
 
 impl AsteroidBundle {
-    fn new(start_position: (f32, f32), texture: Handle<Image>) -> Self {
+    fn new(
+        size: AsteroidSize,
+        start_position: (f32, f32),
+        velocity: Vec2,
+        texture: Handle<Image>,
+        tuning: &AsteroidTuning,
+    ) -> Self {
         let (x_start_position, y_start_position) = start_position;
+        let scale = size.sprite_scale();
+        let transform = Transform {
+            translation: vec3(x_start_position, y_start_position, 0.0),
+            scale: vec3(scale, scale, 1.0),
+            ..Default::default()
+        };
 
         Self {
             asteroid: Asteroid,
-            enemy: Enemy(EnemyKind::Asteroid),
-            health: Health(ASTEROID_HEALTH),
-            speed: YSpeed(ASTEROID_SPEED),
-            collider: Collider(Aabb2d::new(
-                vec2(x_start_position, y_start_position),
-                Vec2::from(ASTEROID_COLLIDER_SIZE) / 2.0,
-            )),
+            enemy: Enemy(EnemyKind::Asteroid(size)),
+            health: Health(tuning.tier(size).health),
+            x_speed: XSpeed(velocity.x),
+            y_speed: YSpeed(velocity.y),
+            // Collider size comes from the tuning file because it should not be given by the sprite
+            collider: Collider::new(
+                CollisionShape::circle(tuning.collider_radius),
+                CollisionLayer::Enemy,
+            ),
             sprite: SpriteBundle {
                 texture,
-                transform: Transform {
-                    translation: vec3(x_start_position, y_start_position, 0.0),
-                    ..Default::default()
-                },
+                transform,
                 ..Default::default()
             },
+            interpolated: Interpolated::new(transform),
+            bounded: Bounded::play_field(ASTEROID_AREA_MARGIN),
         }
     }
 }
 
-pub fn spawn_asteroids_system(
-    time: Res<Time>,
+/// Spawns an asteroid of the given `size` at `position`.
+///
+/// `velocity` holds the horizontal speed and the falling speed of the asteroid.
+pub fn spawn_asteroid(
+    commands: &mut Commands,
+    size: AsteroidSize,
+    position: Vec2,
+    velocity: Vec2,
+    asset_server: &AssetServer,
+    tuning: &AsteroidTuning,
+) {
+    let asteroid_texture = asset_server.load("big_meteor_gray.png");
+    let start_position = (position.x, position.y);
+
+    commands.spawn((
+        AsteroidBundle::new(size, start_position, velocity, asteroid_texture, tuning),
+        StateScoped(InGame),
+    ));
+}
+
+/// Listens for destroyed asteroids and splits them into smaller diverging fragments.
+pub fn split_asteroids_listener(
+    mut enemy_died_event_rx: EventReader<EnemyDiedEvent>,
     asset_server: Res<AssetServer>,
+    tuning: Res<AsteroidTuning>,
     mut commands: Commands,
-    mut asteroids_spawn_timer: ResMut<AsteroidSpawnTimer>,
 ) {
-    if asteroids_spawn_timer.0.tick(time.delta()).just_finished() {
-        let should_spawn = rand::random::<bool>();
-
-        if should_spawn {
-            let asteroid_texture = asset_server.load("big_meteor_gray.png");
-            let start_position = (
-                rand::thread_rng().gen_range(-WINDOW_X_LIMIT..WINDOW_X_LIMIT),
-                rand::thread_rng().gen_range(WINDOW_Y_LIMIT..WINDOW_Y_LIMIT + SPAWN_Y_OFFSET),
-            );
+    for EnemyDiedEvent(kind, position) in enemy_died_event_rx.read() {
+        let EnemyKind::Asteroid(size) = kind else {
+            continue;
+        };
+        let Some(fragment_size) = size.fragment() else {
+            continue;
+        };
+
+        let (min_fragments, max_fragments) = ASTEROID_FRAGMENTS;
+        let fragments = rand::thread_rng().gen_range(min_fragments..=max_fragments);
+        let fragment_speed = tuning.tier(fragment_size).speed;
 
-            commands.spawn(AsteroidBundle::new(start_position, asteroid_texture));
+        for index in 0..fragments {
+            // Spread fragments evenly from left to right
+            let spread = 2.0 * index as f32 / (fragments - 1) as f32 - 1.0;
+            let velocity = vec2(spread * tuning.split_speed, fragment_speed);
+
+            spawn_asteroid(
+                &mut commands,
+                fragment_size,
+                *position,
+                velocity,
+                &asset_server,
+                &tuning,
+            );
         }
     }
 }
 
+/// Updates the falling asteroids when their tuning changes
+///
+/// Health is left untouched on spawned asteroids, as it may already have been damaged.
+pub fn apply_asteroids_tuning_system(
+    tuning: Res<AsteroidTuning>,
+    mut query: Query<(&Enemy, &mut YSpeed, &mut Collider), With<Asteroid>>,
+) {
+    for (enemy, mut speed, mut collider) in &mut query {
+        let EnemyKind::Asteroid(size) = enemy.0 else {
+            continue;
+        };
+
+        speed.0 = tuning.tier(size).speed;
+        collider.shape = CollisionShape::circle(tuning.collider_radius);
+    }
+}
+
 pub fn asteroids_movement_system(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &mut Collider, &YSpeed), With<Asteroid>>,
+    mut query: Query<(&mut Transform, &XSpeed, &YSpeed), With<Asteroid>>,
 ) {
-    for (mut transform, mut collider, speed) in &mut query {
-        // Move sprite
-        transform.translation.y -= speed.0;
-        transform.rotate_z(ASTEROID_ROTATION_SPEED * time.delta_seconds());
+    for (mut transform, x_speed, y_speed) in &mut query {
+        let step = vec2(x_speed.0, -y_speed.0) * time.delta_seconds();
 
-        // Move collider
-        collider.0.translate_by(vec2(0.0, -speed.0))
+        transform.translation += step.extend(0.0);
+        transform.rotate_z(ASTEROID_ROTATION_SPEED * time.delta_seconds());
     }
 }
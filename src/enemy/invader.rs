@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use bevy::{math::vec2, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    collision::{CollisionLayer, CollisionShape},
+    default_config::{WINDOW_X_LIMIT, WINDOW_Y_LIMIT},
+    game_state::InGame,
+    play_area::Bounded,
+    prelude::{Collider, Health},
+    tuning::InvaderTuning,
+};
+
+use super::{wave::SPAWN_Y_OFFSET, Enemy, EnemyKind};
+
+/// Height of the first formation row.
+const FORMATION_TOP: f32 = WINDOW_Y_LIMIT - 110.0;
+pub const FORMATION_ROW_GAP: f32 = 45.0;
+pub const FORMATION_COLUMN_GAP: f32 = 50.0;
+
+pub const INVADER_TOP_ROW_SCORE: u32 = 30;
+pub const INVADER_MIDDLE_ROW_SCORE: u32 = 20;
+pub const INVADER_BOTTOM_ROW_SCORE: u32 = 10;
+
+/// Kind of row an invader belongs to, which defines its looks and score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum InvaderRow {
+    Top,
+    Middle,
+    Bottom,
+}
+
+impl InvaderRow {
+    /// Gets the score given by destroying an invader of this row.
+    pub fn score(&self) -> u32 {
+        match self {
+            InvaderRow::Top => INVADER_TOP_ROW_SCORE,
+            InvaderRow::Middle => INVADER_MIDDLE_ROW_SCORE,
+            InvaderRow::Bottom => INVADER_BOTTOM_ROW_SCORE,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            InvaderRow::Top => Color::srgb(0.9, 0.3, 0.9),
+            InvaderRow::Middle => Color::srgb(0.3, 0.8, 0.9),
+            InvaderRow::Bottom => Color::srgb(0.4, 0.9, 0.3),
+        }
+    }
+}
+
+/// Tags an enemy as a member of the invaders formation.
+#[derive(Component)]
+pub struct Invader;
+
+/// Shared marching state of every `Invader`.
+///
+/// The formation steps sideways every `step_timer` tick, and steps down and turns around
+/// instead when any member would go past `WINDOW_X_LIMIT`. It marches faster as members die.
+#[derive(Resource)]
+pub struct Formation {
+    /// `1.0` when marching right and `-1.0` when marching left.
+    direction: f32,
+    step_timer: Timer,
+    /// Highest amount of members alive at once since the formation was last emptied.
+    members: usize,
+}
+
+impl Default for Formation {
+    fn default() -> Self {
+        Self {
+            direction: 1.0,
+            step_timer: Timer::from_seconds(
+                InvaderTuning::default().step_interval,
+                TimerMode::Repeating,
+            ),
+            members: 0,
+        }
+    }
+}
+
+#[derive(Bundle)]
+struct InvaderBundle {
+    sprite: SpriteBundle,
+    health: Health,
+    invader: Invader,
+    collider: Collider,
+    enemy: Enemy,
+    bounded: Bounded,
+}
+
+impl InvaderBundle {
+    fn new(row: InvaderRow, position: Vec2, tuning: &InvaderTuning) -> Self {
+        let size = Vec2::from(tuning.collider_size);
+
+        Self {
+            invader: Invader,
+            enemy: Enemy(EnemyKind::Invader(row)),
+            health: Health(tuning.health),
+            collider: Collider::new(CollisionShape::rectangle(size), CollisionLayer::Enemy),
+            // Invaders outside the formation spawn above the screen, and their collider has no
+            // bounds until the first collision check
+            bounded: Bounded::play_field(SPAWN_Y_OFFSET + size.y / 2.0),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: row.color(),
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: position.extend(0.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Gets the position of a formation slot, with columns centered in the screen.
+pub fn formation_slot(row: u32, column: u32, columns: u32) -> Vec2 {
+    let first_column_x = -(columns as f32 - 1.0) * FORMATION_COLUMN_GAP / 2.0;
+
+    vec2(
+        first_column_x + column as f32 * FORMATION_COLUMN_GAP,
+        FORMATION_TOP - row as f32 * FORMATION_ROW_GAP,
+    )
+}
+
+/// Spawns an invader of the given `row` kind at `position`, joining the formation.
+pub fn spawn_invader(
+    commands: &mut Commands,
+    row: InvaderRow,
+    position: Vec2,
+    tuning: &InvaderTuning,
+) {
+    commands.spawn((
+        InvaderBundle::new(row, position, tuning),
+        StateScoped(InGame),
+    ));
+}
+
+/// Puts the formation back to its initial marching state.
+pub fn reset_formation_system(mut formation: ResMut<Formation>, tuning: Res<InvaderTuning>) {
+    *formation = Formation::default();
+    formation
+        .step_timer
+        .set_duration(Duration::from_secs_f32(tuning.step_interval));
+}
+
+/// Updates the spawned invaders when their tuning changes
+///
+/// Health is left untouched on spawned invaders, as it may already have been damaged.
+pub fn apply_invaders_tuning_system(
+    tuning: Res<InvaderTuning>,
+    mut query: Query<(&mut Sprite, &mut Collider), With<Invader>>,
+) {
+    let size = Vec2::from(tuning.collider_size);
+
+    for (mut sprite, mut collider) in &mut query {
+        sprite.custom_size = Some(size);
+        collider.shape = CollisionShape::rectangle(size);
+    }
+}
+
+/// Marches the invaders formation sideways, stepping down when it reaches a screen edge.
+pub fn invaders_formation_system(
+    time: Res<Time>,
+    tuning: Res<InvaderTuning>,
+    mut formation: ResMut<Formation>,
+    mut query: Query<&mut Transform, With<Invader>>,
+) {
+    let alive = query.iter().count();
+
+    if alive == 0 {
+        formation.members = 0;
+        return;
+    }
+    formation.members = formation.members.max(alive);
+
+    // The fewer members are left the faster the formation marches
+    let alive_ratio = alive as f32 / formation.members as f32;
+    let step_interval = (tuning.step_interval * alive_ratio).max(tuning.min_step_interval);
+    formation
+        .step_timer
+        .set_duration(Duration::from_secs_f32(step_interval));
+
+    if !formation.step_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let step_x = formation.direction * tuning.step_x;
+    let reaches_edge = query
+        .iter()
+        .any(|transform| (transform.translation.x + step_x).abs() > WINDOW_X_LIMIT);
+
+    let step = if reaches_edge {
+        formation.direction = -formation.direction;
+        vec2(0.0, -tuning.step_y)
+    } else {
+        vec2(step_x, 0.0)
+    };
+
+    for mut transform in &mut query {
+        transform.translation += step.extend(0.0);
+    }
+}
@@ -1,96 +1,228 @@
 use asteroid::{
-    asteroids_movement_system, spawn_asteroids_system, AsteroidSpawnTimer, ASTEROID_SCORE,
-    ASTEROID_SPAWNER_TRIGGER_INTERVAL,
+    apply_asteroids_tuning_system, asteroids_movement_system, spawn_asteroid,
+    split_asteroids_listener, AsteroidSize,
 };
 use bevy::{
-    app::{App, Plugin, PostUpdate, Update},
-    math::bounding::IntersectsVolume,
-    prelude::{Commands, Component, Entity, Event, EventWriter, IntoSystemConfigs, Query, With},
-    time::{Timer, TimerMode},
+    app::{App, FixedPostUpdate, FixedUpdate, Plugin, PreUpdate, Startup, Update},
+    asset::{AssetApp, AssetServer},
+    ecs::system::SystemParam,
+    math::{vec2, Vec2},
+    prelude::{
+        in_state, resource_changed, Commands, Component, Entity, Event, EventReader, EventWriter,
+        Has, IntoSystemConfigs, Query, Res, Transform, With,
+    },
+};
+use invader::{
+    apply_invaders_tuning_system, invaders_formation_system, reset_formation_system, spawn_invader,
+    Formation, InvaderRow,
+};
+use projectile::{
+    apply_enemy_projectiles_tuning_system, arm_enemies_system, enemy_fire_system,
+    enemy_projectiles_movement_system, EnemyProjectile,
+};
+use serde::Deserialize;
+use wave::{
+    check_wave_cleared_system, load_level_system, reset_waves_system, spawn_wave_enemies_system,
+    Level, LevelLoader, WaveClearedEvent, WaveStartedEvent, WaveState,
 };
 
 use crate::{
-    player::shot::Shot,
-    prelude::{Collider, Damage, Health},
+    collision::CollisionStartedEvent,
+    game_state::{despawn_all_system, GameState, NewGameSet},
+    play_area::{EscapeSet, EscapedEvent, Exit},
+    player::shot::{Piercing, Shot},
+    prelude::{Damage, Health},
+    tuning::{AsteroidTuning, EnemyProjectileTuning, InvaderTuning},
 };
 
 pub mod asteroid;
+pub mod invader;
+pub mod projectile;
+pub mod wave;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize)]
 /// Represents the kind of enemy of the game
 pub enum EnemyKind {
-    Asteroid,
+    Asteroid(AsteroidSize),
+    Invader(InvaderRow),
 }
 
 impl EnemyKind {
     /// Gets the corresponding score based on the `EnemyKind`.
     pub fn score(&self) -> u32 {
         match self {
-            EnemyKind::Asteroid => ASTEROID_SCORE,
+            EnemyKind::Asteroid(size) => size.score(),
+            EnemyKind::Invader(row) => row.score(),
         }
     }
 }
 
+/// Sent when an enemy gets past the ship, leaving the play field alive through its bottom.
+#[derive(Event)]
+pub struct EnemyEscapedEvent(pub EnemyKind);
+
 /// Tags an entity as an `Enemy`.
 #[derive(Component)]
 pub struct Enemy(pub EnemyKind);
 
+/// Gathers everything needed to spawn any `EnemyKind`.
+#[derive(SystemParam)]
+pub struct EnemySpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    asset_server: Res<'w, AssetServer>,
+    asteroid_tuning: Res<'w, AsteroidTuning>,
+    invader_tuning: Res<'w, InvaderTuning>,
+}
+
+impl EnemySpawner<'_, '_> {
+    /// Spawns an enemy of the given `kind` at `position`.
+    pub fn spawn(&mut self, kind: &EnemyKind, position: Vec2) {
+        match kind {
+            EnemyKind::Asteroid(size) => spawn_asteroid(
+                &mut self.commands,
+                *size,
+                position,
+                vec2(0.0, self.asteroid_tuning.tier(*size).speed),
+                &self.asset_server,
+                &self.asteroid_tuning,
+            ),
+            EnemyKind::Invader(row) => {
+                spawn_invader(&mut self.commands, *row, position, &self.invader_tuning)
+            }
+        }
+    }
+}
+
 pub struct EnemiesPlugin;
 
 impl Plugin for EnemiesPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(AsteroidSpawnTimer(Timer::from_seconds(
-            ASTEROID_SPAWNER_TRIGGER_INTERVAL,
-            TimerMode::Repeating,
-        )))
-        .add_event::<EnemyDiedEvent>()
-        .add_systems(
-            Update,
-            (
-                spawn_asteroids_system,
-                asteroids_movement_system,
-                enemy_take_damage_system,
+        app.init_asset::<Level>()
+            .register_asset_loader(LevelLoader)
+            .init_resource::<WaveState>()
+            .init_resource::<Formation>()
+            .add_event::<EnemyDiedEvent>()
+            .add_event::<EnemyEscapedEvent>()
+            .add_event::<WaveStartedEvent>()
+            .add_event::<WaveClearedEvent>()
+            .add_systems(Startup, load_level_system)
+            .add_systems(
+                PreUpdate,
+                (
+                    despawn_all_system::<Enemy>,
+                    despawn_all_system::<EnemyProjectile>,
+                    reset_waves_system,
+                    reset_formation_system,
+                )
+                    .in_set(NewGameSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    spawn_wave_enemies_system,
+                    // Fragments must exist before checking whether the wave is cleared
+                    split_asteroids_listener,
+                    check_wave_cleared_system,
+                    asteroids_movement_system,
+                    invaders_formation_system,
+                    arm_enemies_system,
+                    enemy_fire_system,
+                    enemy_projectiles_movement_system,
+                    enemy_take_damage_listener,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
             )
-                .chain(),
-        )
-        .add_systems(PostUpdate, check_enemy_died);
+            .add_systems(
+                Update,
+                (
+                    apply_asteroids_tuning_system.run_if(resource_changed::<AsteroidTuning>),
+                    apply_invaders_tuning_system.run_if(resource_changed::<InvaderTuning>),
+                    apply_enemy_projectiles_tuning_system
+                        .run_if(resource_changed::<EnemyProjectileTuning>),
+                ),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                (check_enemy_died, escaped_enemies_listener.after(EscapeSet))
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
-/// Management systems for common properties between enemies
+// Management systems for common properties between enemies
 
-/// It takes care of compute if some `Shot` and some `Enemy` has been collided and then
-/// reduces the health of the enemy.
-pub fn enemy_take_damage_system(
-    shot_query: Query<(&Damage, &Collider, Entity), With<Shot>>,
-    mut enemy_query: Query<(&mut Health, &Collider), With<Enemy>>,
+/// Listens for shots hitting enemies and reduces the health of the enemies.
+///
+/// A shot only damages the first enemy it touches, as it is consumed by the hit, unless it
+/// is `Piercing`. Piercing shots damage every enemy they go through once.
+pub fn enemy_take_damage_listener(
+    mut collision_started_event_rx: EventReader<CollisionStartedEvent>,
+    shot_query: Query<(&Damage, Has<Piercing>), With<Shot>>,
+    mut enemy_query: Query<&mut Health, With<Enemy>>,
     mut commands: Commands,
 ) {
-    for (mut enemy_health, enemy_collider) in &mut enemy_query {
-        for (shot_damage, shot_collider, shot_entity) in &shot_query {
-            if shot_collider.0.intersects(&enemy_collider.0) {
-                enemy_health.0 -= shot_damage.0;
+    // Shots despawned by an earlier hit of the same tick
+    let mut spent_shots = Vec::new();
 
-                // Instantly despawns shot which has been collided
-                commands.entity(shot_entity).despawn();
-            }
+    for CollisionStartedEvent(shot_entity, enemy_entity) in collision_started_event_rx.read() {
+        let Ok((shot_damage, is_piercing)) = shot_query.get(*shot_entity) else {
+            continue;
+        };
+        let Ok(mut enemy_health) = enemy_query.get_mut(*enemy_entity) else {
+            continue;
+        };
+
+        if spent_shots.contains(shot_entity) {
+            continue;
+        }
+
+        enemy_health.0 -= shot_damage.0;
+
+        if !is_piercing {
+            // Instantly despawns shot which has been collided
+            commands.entity(*shot_entity).despawn();
+            spent_shots.push(*shot_entity);
         }
     }
 }
 
+/// Sent when an enemy is destroyed, with the position where it died.
 #[derive(Event)]
-pub struct EnemyDiedEvent(pub EnemyKind);
+pub struct EnemyDiedEvent(pub EnemyKind, pub Vec2);
 
 /// Checks if enemies health and despawn them if it is equal or lower than 0.
 pub fn check_enemy_died(
-    enemies_query: Query<(&Health, &Enemy, Entity), With<Enemy>>,
+    enemies_query: Query<(&Health, &Enemy, &Transform, Entity), With<Enemy>>,
     mut enemy_died_event_tx: EventWriter<EnemyDiedEvent>,
     mut commands: Commands,
 ) {
-    for (enemy_health, enemy_kind, enemy_entity) in &enemies_query {
+    for (enemy_health, enemy_kind, enemy_transform, enemy_entity) in &enemies_query {
         if enemy_health.0 <= 0.0 {
             commands.entity(enemy_entity).despawn();
-            enemy_died_event_tx.send(EnemyDiedEvent(enemy_kind.0.clone()));
+            enemy_died_event_tx.send(EnemyDiedEvent(
+                enemy_kind.0.clone(),
+                enemy_transform.translation.truncate(),
+            ));
+        }
+    }
+}
+
+/// Tells which enemies escaped, before they are despawned.
+///
+/// Enemies leaving through the sides, such as asteroid fragments, did not get past the ship.
+pub fn escaped_enemies_listener(
+    mut escaped_event_rx: EventReader<EscapedEvent>,
+    enemies_query: Query<&Enemy>,
+    mut enemy_escaped_event_tx: EventWriter<EnemyEscapedEvent>,
+) {
+    for EscapedEvent(entity, exit) in escaped_event_rx.read() {
+        if *exit != Exit::Bottom {
+            continue;
+        }
+
+        if let Ok(enemy) = enemies_query.get(*entity) {
+            enemy_escaped_event_tx.send(EnemyEscapedEvent(enemy.0.clone()));
         }
     }
 }
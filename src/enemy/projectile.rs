@@ -0,0 +1,239 @@
+use bevy::{
+    math::{bounding::BoundingVolume, vec2},
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{
+    collision::{CollisionLayer, CollisionShape},
+    game_state::InGame,
+    play_area::Bounded,
+    player::ship::Ship,
+    prelude::{Collider, Damage, XSpeed, YSpeed},
+    simulation::Interpolated,
+    tuning::EnemyProjectileTuning,
+};
+
+use super::{invader::InvaderRow, Enemy, EnemyKind};
+
+const PROJECTILE_COLOR: Color = Color::srgb(1.0, 0.35, 0.25);
+
+/// Tags a projectile fired by an enemy, which hurts the ship on contact.
+#[derive(Component)]
+pub struct EnemyProjectile;
+
+/// How an enemy aims its projectiles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AimMode {
+    /// A single projectile falling vertically.
+    StraightDown,
+    /// A single projectile heading to where the ship is when fired.
+    AimedAtShip,
+    /// `count` projectiles fanned out downwards across `angle` radians.
+    Spread { count: u32, angle: f32 },
+}
+
+/// Firing behaviour of an `EnemyKind`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnemyWeapon {
+    /// Seconds between shots.
+    pub fire_interval: f32,
+    pub aim: AimMode,
+}
+
+impl EnemyKind {
+    /// Gets the weapon used by this kind of enemy, if it fires at all.
+    pub fn weapon(&self) -> Option<EnemyWeapon> {
+        match self {
+            EnemyKind::Asteroid(_) => None,
+            EnemyKind::Invader(InvaderRow::Bottom) => Some(EnemyWeapon {
+                fire_interval: 3.0,
+                aim: AimMode::StraightDown,
+            }),
+            EnemyKind::Invader(InvaderRow::Middle) => Some(EnemyWeapon {
+                fire_interval: 4.0,
+                aim: AimMode::AimedAtShip,
+            }),
+            EnemyKind::Invader(InvaderRow::Top) => Some(EnemyWeapon {
+                fire_interval: 5.0,
+                aim: AimMode::Spread {
+                    count: 3,
+                    angle: 0.6,
+                },
+            }),
+        }
+    }
+}
+
+/// Cooldown between two shots of an armed enemy.
+#[derive(Component)]
+pub struct EnemyFireTimer(pub Timer);
+
+#[derive(Bundle)]
+struct EnemyProjectileBundle {
+    sprite: SpriteBundle,
+    x_speed: XSpeed,
+    y_speed: YSpeed,
+    damage: Damage,
+    collider: Collider,
+    projectile: EnemyProjectile,
+    interpolated: Interpolated,
+    bounded: Bounded,
+}
+
+impl EnemyProjectileBundle {
+    /// Creates a projectile at `position` moving along `direction`.
+    fn new(position: Vec2, direction: Vec2, tuning: &EnemyProjectileTuning) -> Self {
+        let size = Vec2::from(tuning.collider_size);
+        let transform = Transform::from_translation(position.extend(0.0));
+        let velocity = direction.normalize_or(Vec2::NEG_Y) * tuning.speed;
+
+        Self {
+            projectile: EnemyProjectile,
+            x_speed: XSpeed(velocity.x),
+            // Enemy projectiles fall, so their `YSpeed` points downwards
+            y_speed: YSpeed(-velocity.y),
+            damage: Damage(tuning.damage),
+            collider: Collider::new(
+                CollisionShape::rectangle(size),
+                CollisionLayer::EnemyProjectile,
+            ),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: PROJECTILE_COLOR,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform,
+                ..Default::default()
+            },
+            interpolated: Interpolated::new(transform),
+            bounded: Bounded::play_field(0.0),
+        }
+    }
+}
+
+/// Gives a fire cooldown to new enemies whose kind has a weapon.
+///
+/// The first shot is delayed by a random fraction of the interval so enemies spawned together
+/// do not fire in unison.
+pub fn arm_enemies_system(query: Query<(Entity, &Enemy), Added<Enemy>>, mut commands: Commands) {
+    for (enemy_entity, enemy) in &query {
+        if let Some(weapon) = enemy.0.weapon() {
+            let mut timer = Timer::from_seconds(weapon.fire_interval, TimerMode::Repeating);
+            timer.set_elapsed(timer.duration().mul_f32(rand::thread_rng().gen()));
+
+            commands.entity(enemy_entity).insert(EnemyFireTimer(timer));
+        }
+    }
+}
+
+/// Fires the weapon of armed enemies when their cooldown finishes.
+///
+/// Enemies only fire when no other enemy is right below them, so formations fire from
+/// their front line.
+pub fn enemy_fire_system(
+    time: Res<Time>,
+    tuning: Res<EnemyProjectileTuning>,
+    mut armed_query: Query<(Entity, &Enemy, &Collider, &mut EnemyFireTimer)>,
+    enemies_query: Query<(Entity, &Collider), With<Enemy>>,
+    ship_query: Query<&Transform, With<Ship>>,
+    mut commands: Commands,
+) {
+    let ship_position = ship_query
+        .get_single()
+        .map(|transform| transform.translation.truncate())
+        .ok();
+
+    for (enemy_entity, enemy, enemy_collider, mut fire_timer) in &mut armed_query {
+        if !fire_timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let Some(weapon) = enemy.0.weapon() else {
+            continue;
+        };
+
+        let Some(enemy_bounds) = enemy_collider.bounds() else {
+            continue;
+        };
+        let enemy_position = enemy_bounds.center();
+        let half_width = enemy_bounds.half_size().x;
+        let is_covered = enemies_query.iter().any(|(other_entity, other_collider)| {
+            let Some(other_position) = other_collider.bounds().map(|bounds| bounds.center()) else {
+                return false;
+            };
+
+            other_entity != enemy_entity
+                && other_position.y < enemy_position.y
+                && (other_position.x - enemy_position.x).abs() < half_width
+        });
+
+        if is_covered {
+            continue;
+        }
+
+        let muzzle = enemy_position - vec2(0.0, enemy_bounds.half_size().y);
+
+        for direction in aim_directions(weapon.aim, muzzle, ship_position) {
+            commands.spawn((
+                EnemyProjectileBundle::new(muzzle, direction, &tuning),
+                StateScoped(InGame),
+            ));
+        }
+    }
+}
+
+/// Gets the direction of every projectile fired with `aim` from `muzzle`.
+fn aim_directions(aim: AimMode, muzzle: Vec2, ship_position: Option<Vec2>) -> Vec<Vec2> {
+    match aim {
+        AimMode::StraightDown => vec![Vec2::NEG_Y],
+        AimMode::AimedAtShip => {
+            let direction = ship_position.map_or(Vec2::NEG_Y, |ship| ship - muzzle);
+
+            vec![direction]
+        }
+        AimMode::Spread { count, angle } => {
+            let step = if count > 1 {
+                angle / (count - 1) as f32
+            } else {
+                0.0
+            };
+
+            (0..count)
+                .map(|index| {
+                    let rotation = -angle / 2.0 + step * index as f32;
+
+                    Vec2::from_angle(rotation).rotate(Vec2::NEG_Y)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Handles the enemy projectiles movement
+pub fn enemy_projectiles_movement_system(
+    time: Res<Time>,
+    mut query: Query<(&mut Transform, &XSpeed, &YSpeed), With<EnemyProjectile>>,
+) {
+    for (mut transform, x_speed, y_speed) in &mut query {
+        let step = vec2(x_speed.0, -y_speed.0) * time.delta_seconds();
+
+        transform.translation += step.extend(0.0);
+    }
+}
+
+/// Updates the projectiles in flight when their tuning changes, keeping their heading.
+pub fn apply_enemy_projectiles_tuning_system(
+    tuning: Res<EnemyProjectileTuning>,
+    mut query: Query<(&mut XSpeed, &mut YSpeed, &mut Damage, &mut Collider), With<EnemyProjectile>>,
+) {
+    for (mut x_speed, mut y_speed, mut damage, mut collider) in &mut query {
+        let velocity = vec2(x_speed.0, y_speed.0).normalize_or(Vec2::Y) * tuning.speed;
+
+        x_speed.0 = velocity.x;
+        y_speed.0 = velocity.y;
+        damage.0 = tuning.damage;
+        collider.shape = CollisionShape::rectangle(Vec2::from(tuning.collider_size));
+    }
+}
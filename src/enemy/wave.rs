@@ -0,0 +1,422 @@
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    math::vec2,
+    prelude::*,
+};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    default_config::{WINDOW_X_LIMIT, WINDOW_Y_LIMIT},
+    player::weapon::WeaponReward,
+};
+
+use super::{
+    invader::{formation_slot, FORMATION_COLUMN_GAP},
+    Enemy, EnemyKind, EnemySpawner,
+};
+
+/// Path, relative to the assets folder, of the level played in every run.
+pub const LEVEL_PATH: &str = "levels/main.level.ron";
+
+/// Enemies not placed in the formation spawn up to this distance above the top of the screen.
+pub const SPAWN_Y_OFFSET: f32 = 45.0;
+
+/// Enemies which fell below this height are not considered part of the wave anymore.
+const WAVE_BOTTOM_LIMIT: f32 = -WINDOW_Y_LIMIT;
+
+/// Sequence of waves making up a run, loaded from `LEVEL_PATH`.
+///
+/// Once the last wave is cleared the level starts again from the first one, while the wave
+/// counter keeps growing.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    pub waves: Vec<Wave>,
+}
+
+/// Timed spawn groups which have to be cleared before moving to the next wave.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Wave {
+    /// Seconds to wait before the wave starts.
+    #[serde(default)]
+    pub intermission: f32,
+    /// Weapon change granted to the ship once the wave is cleared.
+    #[serde(default)]
+    pub reward: Option<WeaponReward>,
+    /// Whether the bunkers are built again from scratch when the wave starts.
+    #[serde(default)]
+    pub rebuild_bunkers: bool,
+    pub groups: Vec<SpawnGroup>,
+}
+
+/// Group of enemies of the same kind spawned together within a wave.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnGroup {
+    /// Seconds since the wave started when the first enemy of the group spawns.
+    #[serde(default)]
+    pub at: f32,
+    pub kind: EnemyKind,
+    pub count: u32,
+    pub pattern: SpawnPattern,
+    /// Seconds between two consecutive enemies of the group.
+    #[serde(default)]
+    pub interval: f32,
+}
+
+/// Placement of the enemies of a `SpawnGroup`.
+///
+/// Every pattern but `Formation` spawns the enemies right above the top of the screen.
+#[derive(Deserialize, Clone, Debug)]
+pub enum SpawnPattern {
+    /// Random positions across the screen.
+    Random,
+    /// Evenly spread across the screen.
+    Row,
+    /// Explicit x positions, one per enemy.
+    Positions(Vec<f32>),
+    /// One row of the invaders formation, with one column per enemy. Every `Formation`
+    /// group of a wave is placed one row below the previous one.
+    Formation,
+}
+
+impl SpawnGroup {
+    /// Gets the position of the `index`-th enemy of the group.
+    ///
+    /// `formation_row` is the formation row assigned to the group if it uses
+    /// `SpawnPattern::Formation`.
+    fn position(&self, index: u32, formation_row: u32) -> Vec2 {
+        let x_position = match &self.pattern {
+            SpawnPattern::Random => rand::thread_rng().gen_range(-WINDOW_X_LIMIT..WINDOW_X_LIMIT),
+            SpawnPattern::Row => {
+                let slot_width = 2.0 * WINDOW_X_LIMIT / self.count as f32;
+
+                -WINDOW_X_LIMIT + slot_width * (index as f32 + 0.5)
+            }
+            SpawnPattern::Positions(positions) => positions[index as usize],
+            SpawnPattern::Formation => return formation_slot(formation_row, index, self.count),
+        };
+
+        vec2(
+            x_position,
+            rand::thread_rng().gen_range(WINDOW_Y_LIMIT..WINDOW_Y_LIMIT + SPAWN_Y_OFFSET),
+        )
+    }
+}
+
+impl Level {
+    /// Gets the wave played as the one based `number`-th wave of the run.
+    pub fn wave(&self, number: u32) -> &Wave {
+        &self.waves[number.saturating_sub(1) as usize % self.waves.len()]
+    }
+
+    /// Checks every wave is playable, naming the first invalid field otherwise.
+    pub fn validate(&self) -> Result<(), LevelLoaderError> {
+        if self.waves.is_empty() {
+            return Err(LevelLoaderError::invalid(
+                "waves",
+                "expected at least one wave",
+            ));
+        }
+
+        for (wave_index, wave) in self.waves.iter().enumerate() {
+            let wave_field = format!("waves[{wave_index}]");
+
+            ensure_seconds(format!("{wave_field}.intermission"), wave.intermission)?;
+
+            if wave.groups.is_empty() {
+                return Err(LevelLoaderError::invalid(
+                    format!("{wave_field}.groups"),
+                    "expected at least one spawn group",
+                ));
+            }
+
+            for (group_index, group) in wave.groups.iter().enumerate() {
+                group.validate(&format!("{wave_field}.groups[{group_index}]"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SpawnGroup {
+    fn validate(&self, group_field: &str) -> Result<(), LevelLoaderError> {
+        if self.count == 0 {
+            return Err(LevelLoaderError::invalid(
+                format!("{group_field}.count"),
+                "expected at least one enemy",
+            ));
+        }
+
+        ensure_seconds(format!("{group_field}.at"), self.at)?;
+        ensure_seconds(format!("{group_field}.interval"), self.interval)?;
+
+        if let SpawnPattern::Positions(positions) = &self.pattern {
+            if positions.len() != self.count as usize {
+                return Err(LevelLoaderError::invalid(
+                    format!("{group_field}.pattern"),
+                    format!(
+                        "expected {} positions to match `count`, got {}",
+                        self.count,
+                        positions.len()
+                    ),
+                ));
+            }
+
+            if !positions.iter().all(|x| x.abs() <= WINDOW_X_LIMIT) {
+                return Err(LevelLoaderError::invalid(
+                    format!("{group_field}.pattern"),
+                    format!("expected positions between -{WINDOW_X_LIMIT} and {WINDOW_X_LIMIT}"),
+                ));
+            }
+        }
+
+        if let SpawnPattern::Formation = self.pattern {
+            let formation_width = (self.count - 1) as f32 * FORMATION_COLUMN_GAP;
+
+            if formation_width > 2.0 * WINDOW_X_LIMIT {
+                return Err(LevelLoaderError::invalid(
+                    format!("{group_field}.count"),
+                    format!("{} columns do not fit in the screen", self.count),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn ensure_seconds(field: String, value: f32) -> Result<(), LevelLoaderError> {
+    if value >= 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(LevelLoaderError::invalid(
+            field,
+            format!("expected a non negative number of seconds, got {value}"),
+        ))
+    }
+}
+
+/// Errors raised while loading a `Level` file.
+#[derive(Debug)]
+pub enum LevelLoaderError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    /// The file is well formed but `field` holds a value the game cannot use.
+    Invalid {
+        field: String,
+        reason: String,
+    },
+}
+
+impl LevelLoaderError {
+    fn invalid(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        LevelLoaderError::Invalid {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for LevelLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelLoaderError::Io(error) => write!(f, "could not read level file: {error}"),
+            LevelLoaderError::Parse(error) => write!(f, "could not parse level file: {error}"),
+            LevelLoaderError::Invalid { field, reason } => {
+                write!(f, "invalid level field `{field}`: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelLoaderError {}
+
+impl From<std::io::Error> for LevelLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        LevelLoaderError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for LevelLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        LevelLoaderError::Parse(error)
+    }
+}
+
+/// Loads and validates `.level.ron` files.
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let level = ron::de::from_bytes::<Level>(&bytes)?;
+        level.validate()?;
+
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Keeps the played level loaded for the whole lifetime of the app.
+#[derive(Resource)]
+pub struct LevelHandle(pub Handle<Level>);
+
+/// Sent when a wave starts, holding its one based number.
+#[derive(Event)]
+pub struct WaveStartedEvent(pub u32);
+
+/// Sent when every enemy of a wave has been spawned and none of them is left on screen,
+/// holding its one based number.
+#[derive(Event)]
+pub struct WaveClearedEvent(pub u32);
+
+/// Progress of the run through the level waves.
+#[derive(Resource, Default)]
+pub struct WaveState {
+    /// One based number of the current wave, `0` before the first wave starts.
+    pub number: u32,
+    phase: WavePhase,
+}
+
+enum WavePhase {
+    /// Waiting for the next wave to start.
+    Intermission { elapsed: f32 },
+    /// Spawning enemies of the current wave, sorted by descending spawn time.
+    Active {
+        elapsed: f32,
+        pending: Vec<PendingSpawn>,
+    },
+}
+
+impl Default for WavePhase {
+    fn default() -> Self {
+        WavePhase::Intermission { elapsed: 0.0 }
+    }
+}
+
+struct PendingSpawn {
+    at: f32,
+    kind: EnemyKind,
+    position: Vec2,
+}
+
+impl Wave {
+    /// Lays out every enemy of the wave, the first one to spawn being the last one.
+    fn pending_spawns(&self) -> Vec<PendingSpawn> {
+        let mut pending = Vec::new();
+        let mut formation_rows = 0;
+
+        for group in &self.groups {
+            for index in 0..group.count {
+                pending.push(PendingSpawn {
+                    at: group.at + group.interval * index as f32,
+                    kind: group.kind.clone(),
+                    position: group.position(index, formation_rows),
+                });
+            }
+
+            if let SpawnPattern::Formation = group.pattern {
+                formation_rows += 1;
+            }
+        }
+
+        pending.sort_by(|a, b| b.at.total_cmp(&a.at));
+        pending
+    }
+}
+
+/// Starts loading the level file.
+pub fn load_level_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LevelHandle(asset_server.load(LEVEL_PATH)));
+}
+
+/// Puts the run back at the intermission before the first wave.
+pub fn reset_waves_system(mut wave_state: ResMut<WaveState>) {
+    *wave_state = WaveState::default();
+}
+
+/// Moves the run through the level: waits the intermission, starts the next wave and spawns
+/// its enemies when their time comes.
+pub fn spawn_wave_enemies_system(
+    time: Res<Time>,
+    levels: Res<Assets<Level>>,
+    level_handle: Res<LevelHandle>,
+    mut wave_state: ResMut<WaveState>,
+    mut wave_started_event_tx: EventWriter<WaveStartedEvent>,
+    mut enemy_spawner: EnemySpawner,
+) {
+    // Nothing to play until the level has been loaded
+    let Some(level) = levels.get(&level_handle.0) else {
+        return;
+    };
+    let wave_state = &mut *wave_state;
+
+    let next_wave = level.wave(wave_state.number + 1);
+    let delta = time.delta_seconds();
+
+    match &mut wave_state.phase {
+        WavePhase::Intermission { elapsed } => {
+            *elapsed += delta;
+
+            if *elapsed >= next_wave.intermission {
+                wave_state.phase = WavePhase::Active {
+                    elapsed: 0.0,
+                    pending: next_wave.pending_spawns(),
+                };
+                wave_state.number += 1;
+                wave_started_event_tx.send(WaveStartedEvent(wave_state.number));
+            }
+        }
+        WavePhase::Active { elapsed, pending } => {
+            *elapsed += delta;
+
+            while pending.last().is_some_and(|spawn| spawn.at <= *elapsed) {
+                let spawn = pending.pop().unwrap();
+
+                enemy_spawner.spawn(&spawn.kind, spawn.position);
+            }
+        }
+    }
+}
+
+/// Finishes the current wave once all of its enemies have been spawned and are either dead
+/// or gone past the bottom of the screen.
+pub fn check_wave_cleared_system(
+    enemies_query: Query<&Transform, With<Enemy>>,
+    mut wave_state: ResMut<WaveState>,
+    mut wave_cleared_event_tx: EventWriter<WaveClearedEvent>,
+) {
+    let WavePhase::Active { pending, .. } = &wave_state.phase else {
+        return;
+    };
+
+    let enemies_left = enemies_query
+        .iter()
+        .any(|transform| transform.translation.y > WAVE_BOTTOM_LIMIT);
+
+    if pending.is_empty() && !enemies_left {
+        wave_state.phase = WavePhase::default();
+        wave_cleared_event_tx.send(WaveClearedEvent(wave_state.number));
+    }
+}
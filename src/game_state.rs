@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use crate::input::{Action, ActionState};
+
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_state::<GameState>()
+            .add_computed_state::<InGame>()
+            .enable_state_scoped_entities::<GameState>()
+            .enable_state_scoped_entities::<InGame>()
+            .add_event::<NewGameEvent>()
+            .configure_sets(PreUpdate, NewGameSet.run_if(on_event::<NewGameEvent>()))
+            .add_systems(Update, finish_boot_system.run_if(in_state(GameState::Boot)))
+            .add_systems(
+                FixedUpdate,
+                toggle_pause_system
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Paused))),
+            );
+    }
+}
+
+/// Represents the high level flow of the game.
+///
+/// Gameplay systems only run in `GameState::Playing`.
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum GameState {
+    #[default]
+    Boot,
+    MainMenu,
+    Settings,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+/// Computed state which exists while a run is in progress, either playing or paused.
+///
+/// Entities belonging to a run are scoped to it with `StateScoped(InGame)`, so they are
+/// despawned when the run finishes and the next one starts clean.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = GameState;
+
+    fn compute(sources: GameState) -> Option<Self> {
+        match sources {
+            GameState::Playing | GameState::Paused => Some(InGame),
+            _ => None,
+        }
+    }
+}
+
+/// Requests a fresh run.
+///
+/// Every gameplay plugin resets its entities, counters and timers when it is received, so a
+/// new run can be started from any menu without relaunching the game.
+#[derive(Event)]
+pub struct NewGameEvent;
+
+/// Systems which reset a plugin for a new run.
+///
+/// The set only runs on frames where a `NewGameEvent` has been sent, and it is placed in
+/// `PreUpdate` so the fresh entities exist before any gameplay system runs.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NewGameSet;
+
+/// Despawns every entity tagged with the `T` component.
+pub fn despawn_all_system<T: Component>(query: Query<Entity, With<T>>, mut commands: Commands) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Leaves the boot state once the app has started.
+fn finish_boot_system(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::MainMenu);
+}
+
+/// Switches between `Playing` and `Paused` when the pause action is pressed.
+fn toggle_pause_system(
+    action_state: Res<ActionState>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if action_state.just_pressed(Action::Pause) {
+        match state.get() {
+            GameState::Playing => next_state.set(GameState::Paused),
+            GameState::Paused => next_state.set(GameState::Playing),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use bevy::{
+    app::PluginsState, input::InputPlugin, log::LogPlugin, prelude::*, state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+
+use crate::{
+    game_state::{GameState, NewGameEvent},
+    player::shot::FireMode,
+    simulation::SIMULATION_TIMESTEP,
+};
+
+/// Simulated duration of every headless tick, which runs exactly one simulation step.
+pub const HEADLESS_TICK_DURATION: Duration = SIMULATION_TIMESTEP;
+
+/// Runs the game logic without a window, a camera or any rendering.
+///
+/// It is the headless counterpart of `DefaultConfigPlugins`. Time advances by
+/// `HEADLESS_TICK_DURATION` on every update, so runs are reproducible no matter how fast the
+/// host machine is, and runs start by themselves instead of waiting in the main menu. As
+/// nobody presses any key, the ship fires in `FireMode::Auto`.
+pub struct HeadlessConfigPlugins;
+
+impl Plugin for HeadlessConfigPlugins {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            LogPlugin::default(),
+            // There is nobody around to edit assets while simulating
+            AssetPlugin {
+                watch_for_changes_override: Some(false),
+                ..Default::default()
+            },
+            ImagePlugin::default(),
+            InputPlugin,
+            StatesPlugin,
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(HEADLESS_TICK_DURATION))
+        .insert_resource(FireMode::Auto)
+        .add_systems(
+            Update,
+            autoplay_system
+                .run_if(in_state(GameState::MainMenu).or_else(in_state(GameState::GameOver))),
+        );
+    }
+}
+
+/// Starts a new run whenever the game is waiting in a menu.
+fn autoplay_system(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut new_game_event_tx: EventWriter<NewGameEvent>,
+) {
+    new_game_event_tx.send(NewGameEvent);
+    next_state.set(GameState::Playing);
+}
+
+/// Finishes building `app` and advances it by `ticks` updates.
+///
+/// It can be called several times on the same app, which allows tests and benchmarks to
+/// inspect the world between runs.
+pub fn run_headless(app: &mut App, ticks: u32) {
+    if app.plugins_state() != PluginsState::Cleaned {
+        while app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+
+        app.finish();
+        app.cleanup();
+    }
+
+    for _ in 0..ticks {
+        app.update();
+    }
+}
@@ -0,0 +1,244 @@
+use std::{cmp::Reverse, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::{data_path, load_ron, save_ron, PersistenceError};
+
+/// Name of the file, inside the platform data folder, where high scores are saved.
+pub const HIGH_SCORES_FILE_NAME: &str = "high_scores.ron";
+
+/// Number of best runs kept in the table.
+pub const MAX_HIGH_SCORES: usize = 10;
+/// Most characters the player can use to sign a high score.
+pub const MAX_INITIALS_LENGTH: usize = 3;
+
+/// Keeps the table of best runs, loaded from the data folder on startup.
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<HighScores>()
+            .add_systems(Startup, load_high_scores_system);
+    }
+}
+
+/// A run which made it into the `HighScores` table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub initials: String,
+    pub score: u32,
+}
+
+/// Best runs ever played, sorted from the highest score.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HighScores(Vec<HighScore>);
+
+impl HighScores {
+    pub fn entries(&self) -> &[HighScore] {
+        &self.0
+    }
+
+    /// Gets the highest score of the table, if any run has been saved.
+    pub fn best(&self) -> Option<u32> {
+        self.0.first().map(|high_score| high_score.score)
+    }
+
+    /// Whether a run ending with `score` earns a place in the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.0.len() < MAX_HIGH_SCORES
+                || self.0.last().is_some_and(|lowest| score > lowest.score))
+    }
+
+    /// Adds a run to the table, below the runs with the same score, and drops the runs which
+    /// do not fit anymore.
+    pub fn insert(&mut self, initials: &str, score: u32) {
+        let position = self
+            .0
+            .partition_point(|high_score| high_score.score >= score);
+
+        self.0.insert(
+            position,
+            HighScore {
+                initials: sanitize_initials(initials),
+                score,
+            },
+        );
+        self.0.truncate(MAX_HIGH_SCORES);
+    }
+
+    /// Reads the high scores saved in the data folder, if any.
+    pub fn load() -> Result<Option<Self>, PersistenceError> {
+        Self::load_from(&data_path(HIGH_SCORES_FILE_NAME).ok_or(PersistenceError::NoDir)?)
+    }
+
+    /// Reads the high scores saved at `path`, if any.
+    ///
+    /// Tables edited by hand are tidied up, so they are sorted and fit `MAX_HIGH_SCORES`.
+    pub fn load_from(path: &Path) -> Result<Option<Self>, PersistenceError> {
+        let Some(mut high_scores) = load_ron::<HighScores>(path)? else {
+            return Ok(None);
+        };
+
+        for high_score in &mut high_scores.0 {
+            high_score.initials = sanitize_initials(&high_score.initials);
+        }
+        high_scores
+            .0
+            .sort_by_key(|high_score| Reverse(high_score.score));
+        high_scores.0.truncate(MAX_HIGH_SCORES);
+
+        Ok(Some(high_scores))
+    }
+
+    /// Saves the high scores in the data folder, creating it if needed.
+    pub fn save(&self) -> Result<(), PersistenceError> {
+        self.save_to(&data_path(HIGH_SCORES_FILE_NAME).ok_or(PersistenceError::NoDir)?)
+    }
+
+    /// Saves the high scores at `path`, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), PersistenceError> {
+        save_ron(self, path)
+    }
+}
+
+/// Keeps the first `MAX_INITIALS_LENGTH` letters and digits of `initials`, in uppercase.
+pub fn sanitize_initials(initials: &str) -> String {
+    initials
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .take(MAX_INITIALS_LENGTH)
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Replaces the empty table with the saved one, if any.
+///
+/// A corrupt file is left untouched until the next high score overwrites it.
+fn load_high_scores_system(mut high_scores: ResMut<HighScores>) {
+    match HighScores::load() {
+        Ok(Some(saved_high_scores)) => *high_scores = saved_high_scores,
+        Ok(None) => {}
+        Err(error) => warn!("Starting with an empty high scores table: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    /// Gets a path in the temporary folder which no other test uses.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("rusty-space-invaders-{}", std::process::id()))
+            .join(name)
+    }
+
+    fn table(scores: &[u32]) -> HighScores {
+        let mut high_scores = HighScores::default();
+
+        for score in scores {
+            high_scores.insert("AAA", *score);
+        }
+
+        high_scores
+    }
+
+    fn scores(high_scores: &HighScores) -> Vec<u32> {
+        high_scores
+            .entries()
+            .iter()
+            .map(|high_score| high_score.score)
+            .collect()
+    }
+
+    #[test]
+    fn load_finds_nothing_without_file() {
+        let loaded = HighScores::load_from(&temp_path("missing.ron")).unwrap();
+
+        assert!(loaded.is_none());
+    }
+
+    #[test]
+    fn load_fails_on_garbage_file() {
+        let path = temp_path("garbage.ron");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "([(initials: \"AB\", score: ").unwrap();
+
+        let error = HighScores::load_from(&path).unwrap_err();
+
+        assert!(matches!(error, PersistenceError::Parse(_)));
+    }
+
+    #[test]
+    fn saved_table_loads_back() {
+        let path = temp_path("saved.ron");
+        let high_scores = table(&[30, 10, 20]);
+
+        high_scores.save_to(&path).unwrap();
+        let loaded = HighScores::load_from(&path).unwrap().unwrap();
+
+        assert_eq!(loaded.entries(), high_scores.entries());
+    }
+
+    #[test]
+    fn load_tidies_up_edited_table() {
+        let path = temp_path("edited.ron");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let entries = (1..=12)
+            .map(|score| format!("(initials: \"abcd\", score: {score})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        fs::write(&path, format!("([{entries}])")).unwrap();
+
+        let loaded = HighScores::load_from(&path).unwrap().unwrap();
+
+        assert_eq!(scores(&loaded), (3..=12).rev().collect::<Vec<_>>());
+        assert!(loaded
+            .entries()
+            .iter()
+            .all(|high_score| high_score.initials == "ABC"));
+    }
+
+    #[test]
+    fn insert_keeps_best_scores_sorted() {
+        let mut high_scores = table(&[50, 10, 30, 20, 40, 60, 80, 70, 90, 100]);
+
+        high_scores.insert("NEW", 55);
+
+        assert_eq!(
+            scores(&high_scores),
+            vec![100, 90, 80, 70, 60, 55, 50, 40, 30, 20]
+        );
+        assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+    }
+
+    #[test]
+    fn insert_puts_ties_below_older_runs() {
+        let mut high_scores = table(&[20, 10]);
+
+        high_scores.insert("NEW", 20);
+
+        assert_eq!(scores(&high_scores), vec![20, 20, 10]);
+        assert_eq!(high_scores.entries()[1].initials, "NEW");
+    }
+
+    #[test]
+    fn qualifies_while_table_is_not_full() {
+        let high_scores = table(&[20, 10]);
+
+        assert!(high_scores.qualifies(1));
+        assert!(!high_scores.qualifies(0));
+    }
+
+    #[test]
+    fn tie_with_lowest_score_does_not_qualify_on_full_table() {
+        let high_scores = table(&[10; MAX_HIGH_SCORES]);
+
+        assert!(!high_scores.qualifies(10));
+        assert!(high_scores.qualifies(11));
+    }
+}
@@ -0,0 +1,371 @@
+use std::{collections::BTreeMap, fmt};
+
+use bevy::{
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_state::GameState,
+    persistence::{config_path, load_ron, save_ron, PersistenceError},
+};
+
+/// Name of the file, inside the platform config folder, where bindings are saved.
+pub const BINDINGS_FILE_NAME: &str = "input.ron";
+
+/// Gamepad axes count as pressed once pushed this far from the center.
+pub const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ActionBindings>()
+            .init_resource::<ActionState>()
+            .add_systems(Startup, load_bindings_system)
+            // Actions are read by the simulation, so presses are tracked per tick
+            .add_systems(FixedPreUpdate, update_action_state_system)
+            .add_systems(Update, gamepad_connection_listener);
+    }
+}
+
+/// What the player wants to do, regardless of the device used for it.
+///
+/// Gameplay systems only read actions through `ActionState`, never raw keys or buttons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+    /// Not used by gameplay yet.
+    Bomb,
+}
+
+impl Action {
+    /// Every action, in settings menu order.
+    pub const ALL: [Action; 5] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::Pause,
+        Action::Bomb,
+    ];
+
+    /// Gets the name shown on the menus.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "MOVE LEFT",
+            Action::MoveRight => "MOVE RIGHT",
+            Action::Fire => "FIRE",
+            Action::Pause => "PAUSE",
+            Action::Bomb => "BOMB",
+        }
+    }
+}
+
+/// Physical input which triggers an `Action`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    /// Gamepad axis pushed towards its positive or negative end.
+    GamepadAxis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+impl Binding {
+    /// Whether both bindings are the same kind of input, such as two gamepad buttons.
+    pub fn is_same_kind(&self, other: &Binding) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key_code) => write!(f, "{key_code:?}"),
+            Binding::GamepadButton(button) => write!(f, "{button:?}"),
+            Binding::GamepadAxis { axis, positive } => {
+                write!(f, "{axis:?}{}", if *positive { "+" } else { "-" })
+            }
+        }
+    }
+}
+
+/// Inputs bound to every action, saved in the platform config folder.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct ActionBindings {
+    pub actions: BTreeMap<Action, Vec<Binding>>,
+    #[serde(default)]
+    pub analog: AnalogResponse,
+}
+
+/// How far gamepad axes have to be pushed and how their strength grows past that.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnalogResponse {
+    /// Deflection, from `0.0` to `1.0`, below which axes are ignored.
+    pub deadzone: f32,
+    /// Exponent applied to the deflection past the deadzone. `1.0` is linear, while higher
+    /// values give finer control around the center.
+    pub curve: f32,
+}
+
+impl Default for AnalogResponse {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.15,
+            curve: 2.0,
+        }
+    }
+}
+
+impl AnalogResponse {
+    pub const MAX_DEADZONE: f32 = 0.9;
+    pub const MIN_CURVE: f32 = 0.5;
+    pub const MAX_CURVE: f32 = 4.0;
+
+    /// Maps a raw axis deflection to an action strength from `0.0` to `1.0`.
+    pub fn apply(&self, deflection: f32) -> f32 {
+        let deadzone = self.deadzone.clamp(0.0, Self::MAX_DEADZONE);
+
+        if deflection <= deadzone {
+            return 0.0;
+        }
+
+        let rescaled = ((deflection - deadzone) / (1.0 - deadzone)).min(1.0);
+
+        rescaled.powf(self.curve.clamp(Self::MIN_CURVE, Self::MAX_CURVE))
+    }
+}
+
+impl Default for ActionBindings {
+    fn default() -> Self {
+        use Binding::*;
+
+        let actions = BTreeMap::from([
+            (
+                Action::MoveLeft,
+                vec![
+                    Key(KeyCode::ArrowLeft),
+                    Key(KeyCode::KeyA),
+                    GamepadButton(GamepadButtonType::DPadLeft),
+                    GamepadAxis {
+                        axis: GamepadAxisType::LeftStickX,
+                        positive: false,
+                    },
+                ],
+            ),
+            (
+                Action::MoveRight,
+                vec![
+                    Key(KeyCode::ArrowRight),
+                    Key(KeyCode::KeyD),
+                    GamepadButton(GamepadButtonType::DPadRight),
+                    GamepadAxis {
+                        axis: GamepadAxisType::LeftStickX,
+                        positive: true,
+                    },
+                ],
+            ),
+            (
+                Action::Fire,
+                vec![
+                    Key(KeyCode::Space),
+                    GamepadButton(GamepadButtonType::South),
+                    GamepadButton(GamepadButtonType::RightTrigger2),
+                ],
+            ),
+            (
+                Action::Pause,
+                vec![
+                    Key(KeyCode::Escape),
+                    GamepadButton(GamepadButtonType::Start),
+                ],
+            ),
+            (
+                Action::Bomb,
+                vec![Key(KeyCode::KeyB), GamepadButton(GamepadButtonType::West)],
+            ),
+        ]);
+
+        Self {
+            actions,
+            analog: AnalogResponse::default(),
+        }
+    }
+}
+
+impl ActionBindings {
+    /// Gets the inputs bound to `action`.
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Binds `binding` to `action`, replacing its previous bindings of the same kind.
+    ///
+    /// Binding a gamepad button keeps the gamepad axes bound, so analog movement still works.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.actions.entry(action).or_default();
+
+        bindings.retain(|bound| !bound.is_same_kind(&binding));
+        bindings.push(binding);
+    }
+
+    /// Reads the bindings saved in the config folder, if any.
+    pub fn load() -> Result<Option<Self>, PersistenceError> {
+        load_ron(&config_path(BINDINGS_FILE_NAME).ok_or(PersistenceError::NoDir)?)
+    }
+
+    /// Saves the bindings in the config folder, creating it if needed.
+    pub fn save(&self) -> Result<(), PersistenceError> {
+        save_ron(
+            self,
+            &config_path(BINDINGS_FILE_NAME).ok_or(PersistenceError::NoDir)?,
+        )
+    }
+}
+
+/// Current state of a single `Action`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ActionValue {
+    /// Strength of the action from `0.0` to `1.0`, which is only partial for analog inputs.
+    pub value: f32,
+    pub pressed: bool,
+    pub just_pressed: bool,
+}
+
+/// State of every action for the current frame, gathered from all the bound inputs.
+#[derive(Resource, Default)]
+pub struct ActionState(BTreeMap<Action, ActionValue>);
+
+impl ActionState {
+    pub fn get(&self, action: Action) -> ActionValue {
+        self.0.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.get(action).pressed
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.get(action).just_pressed
+    }
+
+    pub fn value(&self, action: Action) -> f32 {
+        self.get(action).value
+    }
+}
+
+/// Replaces the default bindings with the saved ones, if any.
+fn load_bindings_system(mut bindings: ResMut<ActionBindings>) {
+    match ActionBindings::load() {
+        Ok(Some(saved_bindings)) => *bindings = saved_bindings,
+        Ok(None) => {}
+        Err(error) => warn!("Using default input bindings: {error}"),
+    }
+}
+
+/// Reads every bound input of every connected device and updates the actions.
+fn update_action_state_system(
+    bindings: Res<ActionBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut action_state: ResMut<ActionState>,
+) {
+    for action in Action::ALL {
+        let mut value = 0.0_f32;
+
+        for binding in bindings.get(action) {
+            let binding_value = match *binding {
+                Binding::Key(key_code) => f32::from(u8::from(keys.pressed(key_code))),
+                Binding::GamepadButton(button_type) => gamepads
+                    .iter()
+                    .map(|gamepad| {
+                        let pressed =
+                            gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type));
+
+                        f32::from(u8::from(pressed))
+                    })
+                    .fold(0.0, f32::max),
+                Binding::GamepadAxis { axis, positive } => gamepads
+                    .iter()
+                    .filter_map(|gamepad| gamepad_axes.get(GamepadAxis::new(gamepad, axis)))
+                    .map(|axis_value| {
+                        let deflection = if positive { axis_value } else { -axis_value };
+
+                        bindings.analog.apply(deflection)
+                    })
+                    .fold(0.0, f32::max),
+            };
+
+            value = value.max(binding_value);
+        }
+
+        let was_pressed = action_state.pressed(action);
+        let pressed = value >= AXIS_PRESS_THRESHOLD;
+
+        action_state.0.insert(
+            action,
+            ActionValue {
+                value,
+                pressed,
+                just_pressed: pressed && !was_pressed,
+            },
+        );
+    }
+}
+
+/// Reports controllers plugged or unplugged at any time, pausing the run when one is lost.
+///
+/// Nothing else is needed to support hot-plugging, as actions read every connected gamepad
+/// on every frame.
+fn gamepad_connection_listener(
+    mut gamepad_connection_event_rx: EventReader<GamepadConnectionEvent>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in gamepad_connection_event_rx.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                info!("Gamepad {} connected: {}", event.gamepad.id, info.name);
+            }
+            GamepadConnection::Disconnected => {
+                info!("Gamepad {} disconnected", event.gamepad.id);
+
+                if *state.get() == GameState::Playing {
+                    next_state.set(GameState::Paused);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebinding_a_gamepad_button_keeps_the_stick() {
+        let mut bindings = ActionBindings::default();
+
+        bindings.rebind(
+            Action::MoveLeft,
+            Binding::GamepadButton(GamepadButtonType::DPadDown),
+        );
+
+        let move_left = bindings.get(Action::MoveLeft);
+        assert!(move_left.contains(&Binding::GamepadButton(GamepadButtonType::DPadDown)));
+        assert!(!move_left.contains(&Binding::GamepadButton(GamepadButtonType::DPadLeft)));
+        assert!(move_left.contains(&Binding::GamepadAxis {
+            axis: GamepadAxisType::LeftStickX,
+            positive: false,
+        }));
+        assert!(move_left.contains(&Binding::Key(KeyCode::KeyA)));
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::app::{PluginGroup, PluginGroupBuilder};
+use bunker::BunkerPlugin;
+use collision::CollisionPlugin;
+use enemy::EnemiesPlugin;
+use game_state::GameStatePlugin;
+use high_scores::HighScoresPlugin;
+use input::ActionsPlugin;
+use play_area::PlayAreaPlugin;
+use player::{
+    lives::LivesPlugin, powerup::PowerUpPlugin, ship::ShipPlugin, shot::ShotPlugin,
+    weapon::WeaponPlugin,
+};
+use simulation::SimulationPlugin;
+use tuning::TuningPlugin;
+use ui::UiPlugin;
+
+pub mod bunker;
+pub mod collision;
+pub mod default_config;
+pub mod enemy;
+pub mod game_state;
+pub mod headless;
+pub mod high_scores;
+pub mod input;
+pub mod persistence;
+pub mod play_area;
+pub mod player;
+pub mod prelude;
+pub mod simulation;
+pub mod tuning;
+pub mod ui;
+
+/// Groups every plugin which makes up the game logic and HUD.
+///
+/// It does not set up windowing or rendering, so it has to be added together with either
+/// `DefaultConfigPlugins` to play the game or `HeadlessConfigPlugins` to simulate it.
+/// Single plugins can be disabled or replaced through the `PluginGroupBuilder` API.
+pub struct SpaceInvadersPlugin;
+
+impl PluginGroup for SpaceInvadersPlugin {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(SimulationPlugin)
+            .add(CollisionPlugin)
+            .add(PlayAreaPlugin)
+            .add(GameStatePlugin)
+            .add(ActionsPlugin)
+            .add(TuningPlugin)
+            .add(HighScoresPlugin)
+            .add(UiPlugin)
+            .add(ShipPlugin)
+            .add(LivesPlugin)
+            .add(ShotPlugin)
+            .add(WeaponPlugin)
+            .add(PowerUpPlugin)
+            .add(EnemiesPlugin)
+            .add(BunkerPlugin)
+    }
+}
@@ -1,22 +1,37 @@
-use default_config::DefaultConfigPlugins;
-use enemy::EnemiesPlugin;
-use player::{ship::ShipPlugin, shot::ShotPlugin};
-use ui::UiPlugin;
+use rusty_space_invaders::{
+    default_config::DefaultConfigPlugins,
+    headless::{run_headless, HeadlessConfigPlugins},
+    SpaceInvadersPlugin,
+};
 
-mod default_config;
-mod enemy;
-mod player;
-mod prelude;
-mod ui;
+/// Runs the simulation without a window: `--headless [ticks]`
+const HEADLESS_FLAG: &str = "--headless";
+const DEFAULT_HEADLESS_TICKS: u32 = 3600;
 
 fn main() {
-    bevy::app::App::new()
-        .add_plugins((
-            DefaultConfigPlugins,
-            UiPlugin,
-            ShipPlugin,
-            ShotPlugin,
-            EnemiesPlugin,
-        ))
-        .run();
+    let mut app = bevy::app::App::new();
+
+    match parse_headless_ticks(std::env::args().skip(1)) {
+        Some(ticks) => {
+            app.add_plugins((HeadlessConfigPlugins, SpaceInvadersPlugin));
+            run_headless(&mut app, ticks);
+        }
+        None => {
+            app.add_plugins((DefaultConfigPlugins, SpaceInvadersPlugin))
+                .run();
+        }
+    }
+}
+
+/// Gets the amount of ticks to simulate if the headless flag has been passed.
+fn parse_headless_ticks(mut args: impl Iterator<Item = String>) -> Option<u32> {
+    args.position(|arg| arg == HEADLESS_FLAG)?;
+
+    match args.next() {
+        Some(ticks) => Some(ticks.parse().unwrap_or_else(|_| {
+            eprintln!("`{HEADLESS_FLAG}` expects the amount of ticks to run, got `{ticks}`");
+            std::process::exit(1);
+        })),
+        None => Some(DEFAULT_HEADLESS_TICKS),
+    }
 }
@@ -0,0 +1,84 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Folder holding the game files inside the platform config and data folders.
+const APP_DIR_NAME: &str = "rusty-space-invaders";
+
+/// Gets the path of a settings file, if the platform has a config folder.
+pub fn config_path(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR_NAME).join(file_name))
+}
+
+/// Gets the path of a progress file, if the platform has a data folder.
+pub fn data_path(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join(file_name))
+}
+
+/// Reads the RON file at `path`, or `None` if nothing has been saved there yet.
+pub fn load_ron<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, PersistenceError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+
+    Ok(Some(ron::de::from_bytes(&bytes)?))
+}
+
+/// Writes `value` as RON at `path`, creating its folder if needed.
+pub fn save_ron<T: Serialize>(value: &T, path: &Path) -> Result<(), PersistenceError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(
+        path,
+        ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?,
+    )?;
+
+    Ok(())
+}
+
+/// Errors raised while reading or writing a saved file.
+#[derive(Debug)]
+pub enum PersistenceError {
+    /// The platform does not provide a folder to keep the file in.
+    NoDir,
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Write(ron::Error),
+}
+
+impl fmt::Display for PersistenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistenceError::NoDir => write!(f, "no folder to keep the file in"),
+            PersistenceError::Io(error) => write!(f, "could not access file: {error}"),
+            PersistenceError::Parse(error) => write!(f, "could not parse file: {error}"),
+            PersistenceError::Write(error) => write!(f, "could not write file: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PersistenceError {}
+
+impl From<std::io::Error> for PersistenceError {
+    fn from(error: std::io::Error) -> Self {
+        PersistenceError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for PersistenceError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        PersistenceError::Parse(error)
+    }
+}
+
+impl From<ron::Error> for PersistenceError {
+    fn from(error: ron::Error) -> Self {
+        PersistenceError::Write(error)
+    }
+}
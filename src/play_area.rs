@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use bevy::{math::vec2, prelude::*};
+
+use crate::{
+    default_config::{WINDOW_X_SIZE, WINDOW_Y_SIZE},
+    game_state::GameState,
+    prelude::Collider,
+};
+
+/// Visible part of the world, centered on the origin.
+pub const PLAY_FIELD: Rect = Rect {
+    min: vec2(-WINDOW_X_SIZE / 2.0, -WINDOW_Y_SIZE / 2.0),
+    max: vec2(WINDOW_X_SIZE / 2.0, WINDOW_Y_SIZE / 2.0),
+};
+
+/// Despawns `Bounded` entities which leave their area or outlive their lifetime.
+///
+/// Escaped entities are reported in `FixedPostUpdate` and only despawned in `FixedLast`, so
+/// listeners placed after `EscapeSet` can still query them.
+pub struct PlayAreaPlugin;
+
+impl Plugin for PlayAreaPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<EscapedEvent>()
+            .add_systems(
+                FixedPostUpdate,
+                check_escaped_system
+                    .in_set(EscapeSet)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(FixedLast, despawn_escaped_listener);
+    }
+}
+
+/// Systems which send `EscapedEvent`.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct EscapeSet;
+
+/// Keeps an entity alive only while it touches `area`, and for at most `lifetime` if any.
+///
+/// Entities are measured by their collider bounds when they have one, or by their position.
+#[derive(Component)]
+pub struct Bounded {
+    pub area: Rect,
+    pub lifetime: Option<Timer>,
+}
+
+impl Bounded {
+    /// Bounds an entity to the play field grown by `margin` on every side, so it can be
+    /// spawned or start leaving outside the screen.
+    pub fn play_field(margin: f32) -> Self {
+        Self {
+            area: PLAY_FIELD.inflate(margin),
+            lifetime: None,
+        }
+    }
+
+    /// Also despawns the entity once it has lived for `seconds`.
+    pub fn with_lifetime(mut self, seconds: f32) -> Self {
+        self.lifetime = Some(Timer::new(
+            Duration::from_secs_f32(seconds),
+            TimerMode::Once,
+        ));
+        self
+    }
+}
+
+/// How a `Bounded` entity left its area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    /// It outlived its lifetime, wherever it was.
+    Expired,
+}
+
+/// Sent for an entity which left its area or outlived its lifetime. It is despawned at the end
+/// of the tick.
+#[derive(Event)]
+pub struct EscapedEvent(pub Entity, pub Exit);
+
+/// Ticks the lifetimes and reports the entities which left their area.
+fn check_escaped_system(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Bounded, &Transform, Option<&Collider>)>,
+    mut escaped_event_tx: EventWriter<EscapedEvent>,
+) {
+    for (entity, mut bounded, transform, collider) in &mut query {
+        let expired = bounded
+            .lifetime
+            .as_mut()
+            .is_some_and(|lifetime| lifetime.tick(time.delta()).finished());
+        let (min, max) = match collider.and_then(Collider::bounds) {
+            Some(bounds) => (bounds.min, bounds.max),
+            None => (
+                transform.translation.truncate(),
+                transform.translation.truncate(),
+            ),
+        };
+        let area = bounded.area;
+        let exit = if max.y < area.min.y {
+            Some(Exit::Bottom)
+        } else if min.y > area.max.y {
+            Some(Exit::Top)
+        } else if max.x < area.min.x {
+            Some(Exit::Left)
+        } else if min.x > area.max.x {
+            Some(Exit::Right)
+        } else if expired {
+            Some(Exit::Expired)
+        } else {
+            None
+        };
+
+        if let Some(exit) = exit {
+            escaped_event_tx.send(EscapedEvent(entity, exit));
+        }
+    }
+}
+
+fn despawn_escaped_listener(
+    mut escaped_event_rx: EventReader<EscapedEvent>,
+    mut commands: Commands,
+) {
+    for EscapedEvent(entity, _) in escaped_event_rx.read() {
+        // The entity may have been despawned by gameplay on the same tick
+        if let Some(mut entity_commands) = commands.get_entity(*entity) {
+            entity_commands.despawn();
+        }
+    }
+}
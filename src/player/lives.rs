@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::game_state::{GameState, NewGameSet};
+
+use super::{
+    powerup::{PowerUpCollectedEvent, PowerUpKind},
+    ship::ShipTakeDamageEvent,
+};
+
+/// Lives the player starts every run with.
+pub const STARTING_LIVES: u32 = 3;
+/// Most lives the player can hold thanks to `PowerUpKind::ExtraHeart`.
+pub const MAX_LIVES: u32 = 5;
+
+/// Keeps count of the player lives.
+///
+/// Lives are only changed by gameplay events: every `ShipTakeDamageEvent` takes one away and
+/// every collected `PowerUpKind::ExtraHeart` gives one back. The run is over once none are
+/// left.
+pub struct LivesPlugin;
+
+impl Plugin for LivesPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Lives>()
+            .add_systems(PreUpdate, reset_lives_system.in_set(NewGameSet))
+            .add_systems(
+                FixedPostUpdate,
+                (gain_life_listener, lose_life_listener)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Lives left to the player in the current run.
+#[derive(Resource)]
+pub struct Lives(u32);
+
+impl Lives {
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Lives(STARTING_LIVES)
+    }
+}
+
+/// Gives the player one more life for every collected `PowerUpKind::ExtraHeart`
+fn gain_life_listener(
+    mut power_up_collected_event_rx: EventReader<PowerUpCollectedEvent>,
+    mut lives: ResMut<Lives>,
+) {
+    for PowerUpCollectedEvent(kind) in power_up_collected_event_rx.read() {
+        if *kind == PowerUpKind::ExtraHeart && lives.0 < MAX_LIVES {
+            lives.0 += 1;
+        }
+    }
+}
+
+/// Reduces player's lives by one per hit and ends the run when no lives are left
+fn lose_life_listener(
+    mut ship_take_damage_event_rx: EventReader<ShipTakeDamageEvent>,
+    mut lives: ResMut<Lives>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for _ in ship_take_damage_event_rx.read() {
+        lives.0 = lives.0.saturating_sub(1);
+
+        if lives.0 == 0 {
+            next_state.set(GameState::GameOver);
+        }
+    }
+}
+
+fn reset_lives_system(mut lives: ResMut<Lives>) {
+    *lives = Lives::default();
+}
@@ -1,2 +1,5 @@
+pub mod lives;
+pub mod powerup;
 pub mod ship;
 pub mod shot;
+pub mod weapon;
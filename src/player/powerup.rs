@@ -0,0 +1,303 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    collision::{CollisionLayer, CollisionShape, CollisionStartedEvent},
+    enemy::{asteroid::AsteroidSize, invader::InvaderRow, EnemyDiedEvent, EnemyKind},
+    game_state::{despawn_all_system, GameState, InGame, NewGameSet},
+    play_area::Bounded,
+    prelude::{Collider, YSpeed},
+    simulation::Interpolated,
+    tuning::PowerUpTuning,
+};
+
+use super::ship::Ship;
+
+/// Shooting interval multiplier while `PowerUpKind::RapidFire` is active.
+pub const RAPID_FIRE_FACTOR: f32 = 0.5;
+/// Shot damage multiplier while `PowerUpKind::DamageBoost` is active.
+pub const DAMAGE_BOOST_FACTOR: f32 = 2.0;
+const SHIELD_COLOR: Color = Color::srgb(0.5, 0.9, 1.0);
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ActiveBuffs>()
+            .add_event::<PowerUpCollectedEvent>()
+            .add_systems(
+                PreUpdate,
+                (despawn_all_system::<PowerUp>, reset_buffs_system).in_set(NewGameSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    drop_power_ups_listener,
+                    power_ups_movement_system,
+                    collect_power_ups_listener,
+                    activate_buffs_listener,
+                    tick_buffs_system,
+                    shield_color_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Kinds of power-up the ship can collect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+    /// Gives back a lost life.
+    ExtraHeart,
+    /// Shortens the shooting interval.
+    RapidFire,
+    /// Multiplies the damage of every shot.
+    DamageBoost,
+    /// Adds two diagonal shots to every volley.
+    SpreadShot,
+    /// Absorbs every hit taken by the ship.
+    Shield,
+}
+
+impl PowerUpKind {
+    /// Power-ups which last for a while instead of being applied at once, in HUD order.
+    pub const TIMED: [PowerUpKind; 4] = [
+        PowerUpKind::RapidFire,
+        PowerUpKind::DamageBoost,
+        PowerUpKind::SpreadShot,
+        PowerUpKind::Shield,
+    ];
+
+    /// Gets the name shown on the HUD.
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUpKind::ExtraHeart => "EXTRA HEART",
+            PowerUpKind::RapidFire => "RAPID FIRE",
+            PowerUpKind::DamageBoost => "DAMAGE BOOST",
+            PowerUpKind::SpreadShot => "SPREAD SHOT",
+            PowerUpKind::Shield => "SHIELD",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUpKind::ExtraHeart => Color::srgb(1.0, 0.3, 0.4),
+            PowerUpKind::RapidFire => Color::srgb(1.0, 0.85, 0.2),
+            PowerUpKind::DamageBoost => Color::srgb(1.0, 0.5, 0.1),
+            PowerUpKind::SpreadShot => Color::srgb(0.6, 0.4, 1.0),
+            PowerUpKind::Shield => SHIELD_COLOR,
+        }
+    }
+}
+
+impl EnemyKind {
+    /// Gets the chance of every power-up to drop when this kind of enemy is destroyed.
+    ///
+    /// At most one power-up drops, so chances of a kind must add up to `1.0` or less.
+    pub fn loot(&self) -> &'static [(PowerUpKind, f32)] {
+        match self {
+            EnemyKind::Asteroid(AsteroidSize::Big) => &[
+                (PowerUpKind::ExtraHeart, 0.03),
+                (PowerUpKind::RapidFire, 0.05),
+                (PowerUpKind::DamageBoost, 0.05),
+                (PowerUpKind::SpreadShot, 0.05),
+                (PowerUpKind::Shield, 0.04),
+            ],
+            EnemyKind::Asteroid(AsteroidSize::Medium) => &[
+                (PowerUpKind::RapidFire, 0.03),
+                (PowerUpKind::DamageBoost, 0.03),
+                (PowerUpKind::SpreadShot, 0.03),
+            ],
+            EnemyKind::Asteroid(AsteroidSize::Small) => &[(PowerUpKind::RapidFire, 0.02)],
+            EnemyKind::Invader(InvaderRow::Top) => &[
+                (PowerUpKind::ExtraHeart, 0.04),
+                (PowerUpKind::SpreadShot, 0.06),
+                (PowerUpKind::Shield, 0.06),
+            ],
+            EnemyKind::Invader(InvaderRow::Middle) => &[
+                (PowerUpKind::DamageBoost, 0.04),
+                (PowerUpKind::Shield, 0.03),
+            ],
+            EnemyKind::Invader(InvaderRow::Bottom) => &[(PowerUpKind::RapidFire, 0.04)],
+        }
+    }
+
+    /// Rolls the loot table of this kind, returning the power-up to drop if any.
+    fn roll_loot(&self) -> Option<PowerUpKind> {
+        let mut roll = rand::thread_rng().gen::<f32>();
+
+        for (power_up, chance) in self.loot() {
+            if roll < *chance {
+                return Some(*power_up);
+            }
+            roll -= chance;
+        }
+
+        None
+    }
+}
+
+/// Tags a falling pickup which grants its power-up to the ship on contact.
+#[derive(Component)]
+pub struct PowerUp(pub PowerUpKind);
+
+#[derive(Bundle)]
+struct PowerUpBundle {
+    sprite: SpriteBundle,
+    speed: YSpeed,
+    collider: Collider,
+    power_up: PowerUp,
+    interpolated: Interpolated,
+    bounded: Bounded,
+}
+
+impl PowerUpBundle {
+    fn new(kind: PowerUpKind, position: Vec2, tuning: &PowerUpTuning) -> Self {
+        let size = Vec2::from(tuning.collider_size);
+        let transform = Transform::from_translation(position.extend(0.0));
+
+        Self {
+            power_up: PowerUp(kind),
+            speed: YSpeed(tuning.fall_speed),
+            collider: Collider::new(CollisionShape::rectangle(size), CollisionLayer::Pickup),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform,
+                ..Default::default()
+            },
+            interpolated: Interpolated::new(transform),
+            bounded: Bounded::play_field(0.0),
+        }
+    }
+}
+
+/// Sent when the ship picks up a power-up.
+#[derive(Event)]
+pub struct PowerUpCollectedEvent(pub PowerUpKind);
+
+/// Timed power-ups currently affecting the ship, with the time left for each of them.
+#[derive(Resource, Default)]
+pub struct ActiveBuffs(HashMap<PowerUpKind, Timer>);
+
+impl ActiveBuffs {
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.0.contains_key(&kind)
+    }
+
+    /// Gets the seconds left before `kind` expires, if it is active.
+    pub fn remaining_secs(&self, kind: PowerUpKind) -> Option<f32> {
+        self.0.get(&kind).map(Timer::remaining_secs)
+    }
+
+    /// Gets the shooting interval multiplier given by the active buffs.
+    pub fn fire_rate_factor(&self) -> f32 {
+        if self.is_active(PowerUpKind::RapidFire) {
+            RAPID_FIRE_FACTOR
+        } else {
+            1.0
+        }
+    }
+
+    /// Gets the shot damage multiplier given by the active buffs.
+    pub fn damage_factor(&self) -> f32 {
+        if self.is_active(PowerUpKind::DamageBoost) {
+            DAMAGE_BOOST_FACTOR
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Drops a power-up where an enemy died when its loot table roll succeeds.
+fn drop_power_ups_listener(
+    mut enemy_died_event_rx: EventReader<EnemyDiedEvent>,
+    tuning: Res<PowerUpTuning>,
+    mut commands: Commands,
+) {
+    for EnemyDiedEvent(kind, position) in enemy_died_event_rx.read() {
+        if let Some(power_up) = kind.roll_loot() {
+            commands.spawn((
+                PowerUpBundle::new(power_up, *position, &tuning),
+                StateScoped(InGame),
+            ));
+        }
+    }
+}
+
+/// Makes power-ups fall.
+fn power_ups_movement_system(
+    time: Res<Time>,
+    mut query: Query<(&mut Transform, &YSpeed), With<PowerUp>>,
+) {
+    for (mut transform, speed) in &mut query {
+        transform.translation.y -= speed.0 * time.delta_seconds();
+    }
+}
+
+/// Listens for power-ups and ship collisions and dispatchs `PowerUpCollected` event.
+fn collect_power_ups_listener(
+    mut collision_started_event_rx: EventReader<CollisionStartedEvent>,
+    power_ups_query: Query<&PowerUp>,
+    ship_query: Query<(), With<Ship>>,
+    mut power_up_collected_event_tx: EventWriter<PowerUpCollectedEvent>,
+    mut commands: Commands,
+) {
+    for CollisionStartedEvent(ship_entity, power_up_entity) in collision_started_event_rx.read() {
+        if !ship_query.contains(*ship_entity) {
+            continue;
+        }
+        let Ok(power_up) = power_ups_query.get(*power_up_entity) else {
+            continue;
+        };
+
+        commands.entity(*power_up_entity).despawn();
+        power_up_collected_event_tx.send(PowerUpCollectedEvent(power_up.0));
+    }
+}
+
+/// Starts collected timed buffs, or restarts them when they are already active.
+fn activate_buffs_listener(
+    mut power_up_collected_event_rx: EventReader<PowerUpCollectedEvent>,
+    mut buffs: ResMut<ActiveBuffs>,
+    tuning: Res<PowerUpTuning>,
+) {
+    for PowerUpCollectedEvent(kind) in power_up_collected_event_rx.read() {
+        if PowerUpKind::TIMED.contains(kind) {
+            let duration = Duration::from_secs_f32(tuning.buff_duration);
+
+            buffs.0.insert(*kind, Timer::new(duration, TimerMode::Once));
+        }
+    }
+}
+
+/// Counts down the active buffs and removes the expired ones.
+fn tick_buffs_system(time: Res<Time>, mut buffs: ResMut<ActiveBuffs>) {
+    buffs
+        .0
+        .retain(|_, timer| !timer.tick(time.delta()).finished());
+}
+
+/// Tints the ship while its shield is up.
+fn shield_color_system(buffs: Res<ActiveBuffs>, mut query: Query<&mut Sprite, With<Ship>>) {
+    let color = if buffs.is_active(PowerUpKind::Shield) {
+        SHIELD_COLOR
+    } else {
+        Color::WHITE
+    };
+
+    for mut sprite in &mut query {
+        sprite.color = color;
+    }
+}
+
+/// Clears the buffs of the previous run
+fn reset_buffs_system(mut buffs: ResMut<ActiveBuffs>) {
+    buffs.0.clear();
+}
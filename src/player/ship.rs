@@ -1,12 +1,22 @@
-use bevy::math::bounding::{BoundingVolume, IntersectsVolume};
-use bevy::math::{vec2, vec3};
+use std::time::Duration;
+
+use bevy::app::Plugin;
+use bevy::math::vec3;
 use bevy::prelude::*;
-use bevy::{app::Plugin, math::bounding::Aabb2d};
 
-use crate::enemy::Enemy;
+use crate::collision::{CollisionLayer, CollisionShape, CollisionStartedEvent};
+use crate::enemy::{projectile::EnemyProjectile, Enemy};
+use crate::game_state::{despawn_all_system, GameState, InGame, NewGameSet};
+use crate::input::{Action, ActionState};
+use crate::player::{
+    powerup::{ActiveBuffs, PowerUpKind},
+    weapon::{Weapon, DEFAULT_WEAPON},
+};
 use crate::{
     default_config::{WINDOW_X_LIMIT, WINDOW_Y_SIZE},
-    prelude::{Collider, XSpeed},
+    prelude::{Collider, Health, XSpeed},
+    simulation::{HitStopEvent, Interpolated},
+    tuning::{ShipTuning, WeaponTuning},
 };
 
 pub struct ShipPlugin;
@@ -14,102 +24,243 @@ pub struct ShipPlugin;
 impl Plugin for ShipPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_event::<ShipTakeDamageEvent>()
-            .add_systems(Startup, spawn_ship_system)
-            .add_systems(Update, (ship_movement_system, ship_take_damage_listener));
+            .add_systems(
+                PreUpdate,
+                (despawn_all_system::<Ship>, spawn_ship_system)
+                    .chain()
+                    .in_set(NewGameSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    ship_movement_system,
+                    ship_take_damage_listener,
+                    ship_hit_feedback_listener,
+                    blink_invulnerable_ship_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                destroy_hitters_listener.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                apply_ship_tuning_system.run_if(resource_changed::<ShipTuning>),
+            );
     }
 }
 
 #[derive(Component)]
 pub struct Ship;
 
+/// Keeps the ship from being damaged until the timer finishes, while it blinks.
+#[derive(Component)]
+pub struct Invulnerable(pub Timer);
+
+/// Horizontal speed the ship is pushed at after being hit, on top of its own movement.
+#[derive(Component, Default)]
+pub struct Knockback(pub f32);
+
 #[derive(Bundle)]
 struct ShipBundle {
     speed: XSpeed,
     sprite: SpriteBundle,
     ship: Ship,
     collider: Collider,
+    weapon: Weapon,
+    interpolated: Interpolated,
+    knockback: Knockback,
 }
 
-const SHIP_SPEED: f32 = 5.0;
 const SHIP_SCALE: f32 = 0.75;
+/// Seconds the ship stays visible, then hidden, while blinking.
+const BLINK_INTERVAL: f32 = 0.1;
+/// How fast the knockback fades out, as a fraction of its speed per second.
+const KNOCKBACK_DAMPING: f32 = 10.0;
 const SHIP_POSITION: f32 = -(WINDOW_Y_SIZE / 2.0) + 75.0;
-const SHIP_COLLIDER_SIZE: (f32, f32) = (99.0, 75.0);
 
 impl ShipBundle {
-    pub fn new(texture: Handle<Image>) -> Self {
+    pub fn new(texture: Handle<Image>, tuning: &ShipTuning, weapon: Weapon) -> Self {
+        let transform = Transform {
+            scale: vec3(SHIP_SCALE, SHIP_SCALE, 1.0),
+            translation: vec3(0.0, SHIP_POSITION, 0.0),
+            ..Default::default()
+        };
+
         Self {
             ship: Ship,
-            collider: Collider(Aabb2d::new(
-                vec2(0.0, SHIP_POSITION),
-                Vec2::from(SHIP_COLLIDER_SIZE) / 2.0,
-            )),
-            speed: XSpeed(SHIP_SPEED),
+            weapon,
+            collider: Collider::new(
+                CollisionShape::rectangle(Vec2::from(tuning.collider_size)),
+                CollisionLayer::Player,
+            ),
+            speed: XSpeed(tuning.speed),
             sprite: SpriteBundle {
                 texture,
-                transform: Transform {
-                    scale: vec3(SHIP_SCALE, SHIP_SCALE, 0.0),
-                    translation: vec3(0.0, SHIP_POSITION, 0.0),
-                    ..Default::default()
-                },
+                transform,
                 ..Default::default()
             },
+            interpolated: Interpolated::new(transform),
+            knockback: Knockback::default(),
         }
     }
 }
 
 /// Spawns the player ship in the screen.
-fn spawn_ship_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn spawn_ship_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tuning: Res<ShipTuning>,
+    weapon_tuning: Res<WeaponTuning>,
+) {
     let ship_handle: Handle<Image> = asset_server.load("player_ship.png");
+    let weapon = Weapon::new(DEFAULT_WEAPON, &weapon_tuning, &asset_server);
 
-    commands.spawn(ShipBundle::new(ship_handle));
+    commands.spawn((
+        ShipBundle::new(ship_handle, &tuning, weapon),
+        StateScoped(InGame),
+    ));
 }
 
-/// Handles the user input to move the ship horizontally in the screen
+/// Updates the spawned ship when its tuning changes
+fn apply_ship_tuning_system(
+    tuning: Res<ShipTuning>,
+    mut query: Query<(&mut XSpeed, &mut Collider), With<Ship>>,
+) {
+    for (mut speed, mut collider) in &mut query {
+        speed.0 = tuning.speed;
+        collider.shape = CollisionShape::rectangle(Vec2::from(tuning.collider_size));
+    }
+}
+
+/// Handles the movement actions to move the ship horizontally in the screen
+///
+/// Analog inputs move the ship slower the less they are pushed. Any knockback is added on top
+/// and fades out over time.
 fn ship_movement_system(
-    mut query: Query<(&mut Transform, &mut Collider, &XSpeed), With<Ship>>,
-    key: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut query: Query<(&mut Transform, &mut Knockback, &XSpeed), With<Ship>>,
+    action_state: Res<ActionState>,
 ) {
-    let (mut transform, mut collider, speed) = query.get_single_mut().unwrap();
+    let Ok((mut transform, mut knockback, speed)) = query.get_single_mut() else {
+        return;
+    };
 
-    for key_code in key.get_pressed() {
-        match key_code {
-            KeyCode::ArrowLeft => {
-                let new_position = transform.translation.x - speed.0;
+    let direction = action_state.value(Action::MoveRight) - action_state.value(Action::MoveLeft);
+    let velocity = direction * speed.0 + knockback.0;
+    transform.translation.x = (transform.translation.x + velocity * time.delta_seconds())
+        .clamp(-WINDOW_X_LIMIT, WINDOW_X_LIMIT);
+    knockback.0 *= (-KNOCKBACK_DAMPING * time.delta_seconds()).exp();
+}
 
-                if !(new_position < -WINDOW_X_LIMIT) {
-                    transform.translation.x = new_position;
-                    collider.0.translate_by(vec2(-speed.0, 0.0));
-                }
-            }
-            KeyCode::ArrowRight => {
-                let new_position = transform.translation.x + speed.0;
+/// Sent when the ship is hurt, holding the enemy or projectile which hit it.
+#[derive(Event)]
+pub struct ShipTakeDamageEvent(pub Entity);
+
+/// Listens for enemies or enemy projectiles and ship collisions and dispatchs `ShipTakeDamage`
+/// event.
+///
+/// Enemies hitting a shielded ship are destroyed instead, and projectiles are absorbed. Hits
+/// are ignored while the ship is `Invulnerable`, so it is damaged at most once per tick.
+fn ship_take_damage_listener(
+    mut collision_started_event_rx: EventReader<CollisionStartedEvent>,
+    mut enemies_query: Query<&mut Health, With<Enemy>>,
+    projectiles_query: Query<(), With<EnemyProjectile>>,
+    ship_query: Query<Has<Invulnerable>, With<Ship>>,
+    buffs: Res<ActiveBuffs>,
+    mut ship_take_damage_event_tx: EventWriter<ShipTakeDamageEvent>,
+    mut commands: Commands,
+) {
+    let is_shielded = buffs.is_active(PowerUpKind::Shield);
+    // The ship only becomes `Invulnerable` once commands are applied
+    let mut is_hit = false;
 
-                if !(new_position > WINDOW_X_LIMIT) {
-                    transform.translation.x = new_position;
-                    collider.0.translate_by(vec2(speed.0, 0.0));
-                }
+    for CollisionStartedEvent(ship_entity, hitter_entity) in collision_started_event_rx.read() {
+        let Ok(is_invulnerable) = ship_query.get(*ship_entity) else {
+            continue;
+        };
+        let is_projectile = projectiles_query.contains(*hitter_entity);
+
+        if !is_projectile && !enemies_query.contains(*hitter_entity) {
+            continue;
+        }
+
+        if is_shielded {
+            if is_projectile {
+                commands.entity(*hitter_entity).despawn();
+            } else if let Ok(mut enemy_health) = enemies_query.get_mut(*hitter_entity) {
+                enemy_health.0 = 0.0;
             }
+        } else if !is_invulnerable && !is_hit {
+            ship_take_damage_event_tx.send(ShipTakeDamageEvent(*hitter_entity));
+            is_hit = true;
+        }
+    }
+}
+
+/// Makes the ship invulnerable after a hit, pushes it away from what hit it and freezes the
+/// game for a moment.
+fn ship_hit_feedback_listener(
+    mut ship_take_damage_event_rx: EventReader<ShipTakeDamageEvent>,
+    mut ship_query: Query<(Entity, &Transform, &mut Knockback), With<Ship>>,
+    hitters_query: Query<&Transform>,
+    tuning: Res<ShipTuning>,
+    mut hit_stop_event_tx: EventWriter<HitStopEvent>,
+    mut commands: Commands,
+) {
+    let Ok((ship_entity, ship_transform, mut knockback)) = ship_query.get_single_mut() else {
+        return;
+    };
 
-            // Do nothing for other key presses
-            _ => {}
+    for ShipTakeDamageEvent(hitter_entity) in ship_take_damage_event_rx.read() {
+        if let Ok(hitter_transform) = hitters_query.get(*hitter_entity) {
+            let direction =
+                (ship_transform.translation.x - hitter_transform.translation.x).signum();
+            knockback.0 = direction * tuning.knockback_speed;
         }
+
+        commands
+            .entity(ship_entity)
+            .insert(Invulnerable(Timer::from_seconds(
+                tuning.invulnerability_duration,
+                TimerMode::Once,
+            )));
+        hit_stop_event_tx.send(HitStopEvent(Duration::from_secs_f32(
+            tuning.hit_stop_duration,
+        )));
     }
 }
 
-#[derive(Event)]
-pub struct ShipTakeDamageEvent(pub Entity);
+/// Destroys the enemies and projectiles which damaged the ship.
+fn destroy_hitters_listener(
+    mut ship_take_damage_event_rx: EventReader<ShipTakeDamageEvent>,
+    mut commands: Commands,
+) {
+    for ShipTakeDamageEvent(hitter_entity) in ship_take_damage_event_rx.read() {
+        commands.entity(*hitter_entity).despawn();
+    }
+}
 
-/// Listens for enemies and ship or barrier collisions and dispatchs `ShipTakeDamage` event.
-fn ship_take_damage_listener(
-    enemies_query: Query<(&Collider, Entity), With<Enemy>>,
-    ship_query: Query<&Collider, With<Ship>>,
-    mut ship_take_damage_event_tx: EventWriter<ShipTakeDamageEvent>,
+/// Blinks the ship while it is invulnerable, and makes it vulnerable again once the time is up.
+fn blink_invulnerable_ship_system(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Visibility), With<Ship>>,
+    mut commands: Commands,
 ) {
-    for (enemy_collider, enemy_entity) in &enemies_query {
-        if let Ok(ship_collider) = ship_query.get_single() {
-            if ship_collider.0.intersects(&enemy_collider.0) {
-                ship_take_damage_event_tx.send(ShipTakeDamageEvent(enemy_entity));
-            }
+    for (entity, mut invulnerable, mut visibility) in &mut query {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+            *visibility = Visibility::Inherited;
+            continue;
         }
+
+        let blinks = (invulnerable.0.elapsed_secs() / BLINK_INTERVAL) as u32;
+        *visibility = if blinks.is_multiple_of(2) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
     }
 }
@@ -1,113 +1,215 @@
 use bevy::{
-    app::Update,
-    asset::{AssetServer, Handle},
-    math::{
-        bounding::{Aabb2d, BoundingVolume},
-        vec2, vec3, Vec2,
-    },
+    app::{FixedUpdate, PreUpdate, Update},
+    math::{vec2, vec3, Vec2},
     prelude::{
-        Bundle, Commands, Component, Image, Plugin, Query, Res, ResMut, Resource, Transform, With,
+        in_state, resource_changed, Bundle, Commands, Component, IntoSystemConfigs, Plugin, Quat,
+        Query, Res, Resource, StateScoped, Transform, With,
     },
     sprite::SpriteBundle,
-    time::{Time, Timer, TimerMode},
+    time::Time,
 };
 
-use crate::prelude::{Collider, Damage, YSpeed};
+use crate::{
+    collision::{CollisionLayer, CollisionShape},
+    game_state::{despawn_all_system, GameState, InGame, NewGameSet},
+    input::{Action, ActionState},
+    play_area::Bounded,
+    prelude::{Collider, Damage, XSpeed, YSpeed},
+    simulation::Interpolated,
+    tuning::ShotTuning,
+};
 
-use super::ship::Ship;
+use super::{
+    powerup::{ActiveBuffs, PowerUpKind},
+    ship::Ship,
+    weapon::Weapon,
+};
 
-const BASE_SHOT_DAMAGE: f32 = 1.0;
-const SHOT_SPEED: f32 = 10.0;
-const SHOOTING_INTERVAL: f32 = 0.5;
 const SHOT_SPAWN_OFFSET: f32 = 35.0;
-const SHOT_COLLIDER_SIZE: (f32, f32) = (9.0, 54.0);
+/// Tilt in radians of the diagonal shots added by `PowerUpKind::SpreadShot`.
+const SPREAD_SHOT_ANGLE: f32 = 0.3;
 
 pub struct ShotPlugin;
 
 impl Plugin for ShotPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.insert_resource(ShootingTimer(Timer::from_seconds(
-            SHOOTING_INTERVAL,
-            TimerMode::Repeating,
-        )))
-        .add_systems(Update, (spawn_shot_system, shot_moving_system));
+        app.init_resource::<FireMode>()
+            .add_systems(PreUpdate, despawn_all_system::<Shot>.in_set(NewGameSet))
+            .add_systems(
+                FixedUpdate,
+                (spawn_shot_system, shot_moving_system).run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                apply_shot_tuning_system.run_if(resource_changed::<ShotTuning>),
+            );
     }
 }
 
-#[derive(Resource)]
-struct ShootingTimer(Timer);
+/// How the ship weapon is triggered. Cooldowns apply in every mode.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FireMode {
+    /// One volley for every press of `Action::Fire`.
+    Manual,
+    /// Continuous fire while `Action::Fire` is held.
+    #[default]
+    Hold,
+    /// Continuous fire without touching any key.
+    Auto,
+}
+
+impl FireMode {
+    /// Gets the mode selected after this one when cycling through them.
+    pub fn next(&self) -> Self {
+        match self {
+            FireMode::Manual => FireMode::Hold,
+            FireMode::Hold => FireMode::Auto,
+            FireMode::Auto => FireMode::Manual,
+        }
+    }
+
+    /// Gets the name shown on the menus.
+    pub fn label(&self) -> &'static str {
+        match self {
+            FireMode::Manual => "MANUAL",
+            FireMode::Hold => "HOLD",
+            FireMode::Auto => "AUTO",
+        }
+    }
+}
 
 #[derive(Component)]
 pub struct Shot;
 
+/// Lets a shot go through enemies instead of being consumed by the first hit.
+#[derive(Component)]
+pub struct Piercing;
+
 #[derive(Bundle)]
 struct ShotBundle {
-    speed: YSpeed,
+    x_speed: XSpeed,
+    y_speed: YSpeed,
     sprite: SpriteBundle,
     collider: Collider,
     damage: Damage,
     shot: Shot,
+    interpolated: Interpolated,
+    bounded: Bounded,
 }
 
 impl ShotBundle {
-    pub fn new(shot_offset: (f32, f32), texture: Handle<Image>) -> Self {
+    /// Creates a shot of `weapon` at `shot_offset`, tilted `angle` radians to the left of
+    /// straight up.
+    pub fn new(
+        shot_offset: (f32, f32),
+        angle: f32,
+        damage: f32,
+        weapon: &Weapon,
+        tuning: &ShotTuning,
+    ) -> Self {
         let (x_offset, y_offset) = shot_offset;
+        let velocity = Vec2::from_angle(angle).rotate(Vec2::Y) * weapon.speed;
+        let transform = Transform {
+            translation: vec3(x_offset, y_offset, 0.0),
+            rotation: Quat::from_rotation_z(angle),
+            ..Default::default()
+        };
 
         Self {
             shot: Shot,
-            speed: YSpeed(SHOT_SPEED),
-            damage: Damage(BASE_SHOT_DAMAGE),
-            collider: Collider(Aabb2d::new(
-                vec2(x_offset, y_offset),
-                Vec2::from(SHOT_COLLIDER_SIZE) / 2.0,
-            )),
+            x_speed: XSpeed(velocity.x),
+            y_speed: YSpeed(velocity.y),
+            damage: Damage(damage),
+            collider: Collider::new(
+                CollisionShape::capsule(Vec2::from(tuning.collider_size)),
+                CollisionLayer::PlayerProjectile,
+            ),
             sprite: SpriteBundle {
-                texture,
-                transform: Transform {
-                    translation: vec3(x_offset, y_offset, 0.0),
-                    ..Default::default()
-                },
+                texture: weapon.sprite.clone(),
+                transform,
                 ..Default::default()
             },
+            interpolated: Interpolated::new(transform),
+            bounded: Bounded::play_field(0.0),
         }
     }
 }
 
-/// Spawns player shots on the screen every fixed amount of time
+/// Fires the ship weapon when the `FireMode` asks for it and its cooldown is over
+///
+/// Active buffs shorten the cooldown, raise the damage or add diagonal shots to the volley.
 fn spawn_shot_system(
-    mut shooting_timer: ResMut<ShootingTimer>,
     mut commands: Commands,
-    query: Query<&Transform, With<Ship>>,
-    asset_server: Res<AssetServer>,
+    mut query: Query<(&Transform, &mut Weapon), With<Ship>>,
+    action_state: Res<ActionState>,
+    fire_mode: Res<FireMode>,
     time: Res<Time>,
+    tuning: Res<ShotTuning>,
+    buffs: Res<ActiveBuffs>,
 ) {
-    let shot_texture: Handle<Image> = asset_server.load("laser_base.png");
-    let ship_position = query.get_single().unwrap().translation;
-
-    if shooting_timer.0.tick(time.delta()).just_finished() {
-        commands.spawn(ShotBundle::new(
-            (
-                ship_position.x + SHOT_SPAWN_OFFSET,
-                ship_position.y + SHOT_SPAWN_OFFSET,
-            ),
-            shot_texture.clone(),
-        ));
-        commands.spawn(ShotBundle::new(
-            (
-                ship_position.x - SHOT_SPAWN_OFFSET,
-                ship_position.y + SHOT_SPAWN_OFFSET,
+    let Ok((ship_transform, mut weapon)) = query.get_single_mut() else {
+        return;
+    };
+    let ship_position = ship_transform.translation;
+
+    // Scale the time instead of the duration so the weapon keeps its tuned cooldown
+    let delta = time.delta().div_f32(buffs.fire_rate_factor());
+    weapon.cooldown.tick(delta);
+
+    let is_triggered = match *fire_mode {
+        FireMode::Manual => action_state.just_pressed(Action::Fire),
+        FireMode::Hold => action_state.pressed(Action::Fire),
+        FireMode::Auto => true,
+    };
+
+    if !is_triggered || !weapon.cooldown.finished() {
+        return;
+    }
+    weapon.cooldown.reset();
+
+    let mut volley = weapon.kind.pattern(weapon.level);
+    if buffs.is_active(PowerUpKind::SpreadShot) {
+        volley.extend([(0.0, SPREAD_SHOT_ANGLE), (0.0, -SPREAD_SHOT_ANGLE)]);
+    }
+
+    let damage = weapon.damage * buffs.damage_factor();
+
+    for (x_offset, angle) in volley {
+        let mut shot = commands.spawn((
+            ShotBundle::new(
+                (
+                    ship_position.x + x_offset,
+                    ship_position.y + SHOT_SPAWN_OFFSET,
+                ),
+                angle,
+                damage,
+                &weapon,
+                &tuning,
             ),
-            shot_texture,
+            StateScoped(InGame),
         ));
+
+        if weapon.kind.is_piercing() {
+            shot.insert(Piercing);
+        }
+    }
+}
+
+/// Updates the shots in flight when their tuning changes
+fn apply_shot_tuning_system(tuning: Res<ShotTuning>, mut query: Query<&mut Collider, With<Shot>>) {
+    for mut collider in &mut query {
+        collider.shape = CollisionShape::capsule(Vec2::from(tuning.collider_size));
     }
 }
 
-/// Handles the shots movement vertically
-fn shot_moving_system(mut query: Query<(&mut Transform, &mut Collider, &YSpeed), With<Shot>>) {
-    for (mut transform, mut collider, speed) in &mut query {
-        // Move sprite
-        transform.translation.y += speed.0;
-        // Move collider
-        collider.0.translate_by(vec2(0.0, speed.0));
+/// Handles the shots movement
+fn shot_moving_system(
+    time: Res<Time>,
+    mut query: Query<(&mut Transform, &XSpeed, &YSpeed), With<Shot>>,
+) {
+    for (mut transform, x_speed, y_speed) in &mut query {
+        let step = vec2(x_speed.0, y_speed.0) * time.delta_seconds();
+
+        transform.translation += step.extend(0.0);
     }
 }
@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    enemy::wave::{Level, LevelHandle, WaveClearedEvent},
+    game_state::GameState,
+    tuning::WeaponTuning,
+};
+
+use super::ship::Ship;
+
+/// Weapon the ship starts every run with.
+pub const DEFAULT_WEAPON: WeaponKind = WeaponKind::Twin;
+pub const MAX_WEAPON_LEVEL: u32 = 3;
+
+/// Horizontal distance between the outer shots of a twin volley and the ship center.
+const TWIN_SHOT_OFFSET: f32 = 35.0;
+/// Horizontal distance between two parallel shots of the same volley.
+const PARALLEL_SHOT_GAP: f32 = 20.0;
+/// Angle in radians between two consecutive shots of a spread fan.
+const SPREAD_FAN_STEP: f32 = 0.2;
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<SwapWeaponEvent>()
+            .add_event::<UpgradeWeaponEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    wave_reward_listener,
+                    swap_weapon_listener,
+                    upgrade_weapon_listener,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                apply_weapon_tuning_system.run_if(resource_changed::<WeaponTuning>),
+            );
+    }
+}
+
+/// Kinds of weapon the ship can carry, which define the pattern of its volleys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum WeaponKind {
+    /// One shot from the ship nose.
+    Single,
+    /// Two parallel shots from the ship sides.
+    Twin,
+    /// Shots fanned out from the ship nose.
+    SpreadFan,
+    /// One shot from the ship nose at a short cooldown.
+    Rapid,
+    /// One shot from the ship nose going through every enemy in its way.
+    Piercing,
+}
+
+impl WeaponKind {
+    /// Gets the horizontal offset and the tilt in radians of every shot of a volley fired
+    /// at the given upgrade `level`.
+    pub fn pattern(&self, level: u32) -> Vec<(f32, f32)> {
+        match self {
+            WeaponKind::Single | WeaponKind::Rapid | WeaponKind::Piercing => {
+                parallel_offsets(level)
+                    .into_iter()
+                    .map(|x_offset| (x_offset, 0.0))
+                    .collect()
+            }
+            WeaponKind::Twin => parallel_offsets(level)
+                .into_iter()
+                .flat_map(|x_offset| {
+                    [
+                        (x_offset - TWIN_SHOT_OFFSET, 0.0),
+                        (x_offset + TWIN_SHOT_OFFSET, 0.0),
+                    ]
+                })
+                .collect(),
+            WeaponKind::SpreadFan => {
+                let count = 2 * level + 1;
+                let first_angle = -SPREAD_FAN_STEP * level as f32;
+
+                (0..count)
+                    .map(|index| (0.0, first_angle + SPREAD_FAN_STEP * index as f32))
+                    .collect()
+            }
+        }
+    }
+
+    /// Whether shots of this weapon keep going after hitting an enemy.
+    pub fn is_piercing(&self) -> bool {
+        matches!(self, WeaponKind::Piercing)
+    }
+}
+
+/// Gets `count` horizontal offsets `PARALLEL_SHOT_GAP` apart, centered on the ship.
+fn parallel_offsets(count: u32) -> Vec<f32> {
+    let first_offset = -(count as f32 - 1.0) * PARALLEL_SHOT_GAP / 2.0;
+
+    (0..count)
+        .map(|index| first_offset + PARALLEL_SHOT_GAP * index as f32)
+        .collect()
+}
+
+/// Gun mounted on the ship.
+///
+/// Stats come from the `WeaponTuning` of its kind, while the upgrade level adds shots to
+/// every volley.
+#[derive(Component)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    /// Upgrade level, from `1` to `MAX_WEAPON_LEVEL`.
+    pub level: u32,
+    /// Time left until the weapon can fire again, finished while it is ready.
+    pub cooldown: Timer,
+    pub damage: f32,
+    pub speed: f32,
+    pub sprite: Handle<Image>,
+}
+
+impl Weapon {
+    pub fn new(kind: WeaponKind, tuning: &WeaponTuning, asset_server: &AssetServer) -> Self {
+        let stats = tuning.kind(kind);
+
+        Self {
+            kind,
+            level: 1,
+            cooldown: Timer::from_seconds(stats.cooldown, TimerMode::Once),
+            damage: stats.damage,
+            speed: stats.speed,
+            sprite: asset_server.load(stats.sprite.clone()),
+        }
+    }
+
+    /// Refreshes the stats of the weapon from its tuning, keeping its level and cooldown.
+    fn apply_tuning(&mut self, tuning: &WeaponTuning, asset_server: &AssetServer) {
+        let stats = tuning.kind(self.kind);
+
+        self.cooldown
+            .set_duration(Duration::from_secs_f32(stats.cooldown));
+        self.damage = stats.damage;
+        self.speed = stats.speed;
+        self.sprite = asset_server.load(stats.sprite.clone());
+    }
+}
+
+/// Weapon change granted when a wave is cleared.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum WeaponReward {
+    /// Replaces the ship weapon, going back to the first level.
+    Swap(WeaponKind),
+    /// Raises the ship weapon level.
+    Upgrade,
+}
+
+/// Requests to replace the ship weapon with a new one of the given kind.
+#[derive(Event)]
+pub struct SwapWeaponEvent(pub WeaponKind);
+
+/// Requests to raise the ship weapon level, up to `MAX_WEAPON_LEVEL`.
+#[derive(Event)]
+pub struct UpgradeWeaponEvent;
+
+/// Grants the reward of every cleared wave which has one.
+fn wave_reward_listener(
+    mut wave_cleared_event_rx: EventReader<WaveClearedEvent>,
+    levels: Res<Assets<Level>>,
+    level_handle: Res<LevelHandle>,
+    mut swap_weapon_event_tx: EventWriter<SwapWeaponEvent>,
+    mut upgrade_weapon_event_tx: EventWriter<UpgradeWeaponEvent>,
+) {
+    let Some(level) = levels.get(&level_handle.0) else {
+        return;
+    };
+
+    for WaveClearedEvent(wave_number) in wave_cleared_event_rx.read() {
+        let wave = level.wave(*wave_number);
+
+        match wave.reward {
+            Some(WeaponReward::Swap(kind)) => {
+                swap_weapon_event_tx.send(SwapWeaponEvent(kind));
+            }
+            Some(WeaponReward::Upgrade) => {
+                upgrade_weapon_event_tx.send(UpgradeWeaponEvent);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Replaces the ship weapon when requested.
+fn swap_weapon_listener(
+    mut swap_weapon_event_rx: EventReader<SwapWeaponEvent>,
+    mut query: Query<&mut Weapon, With<Ship>>,
+    tuning: Res<WeaponTuning>,
+    asset_server: Res<AssetServer>,
+) {
+    for SwapWeaponEvent(kind) in swap_weapon_event_rx.read() {
+        if let Ok(mut weapon) = query.get_single_mut() {
+            *weapon = Weapon::new(*kind, &tuning, &asset_server);
+        }
+    }
+}
+
+/// Raises the ship weapon level when requested.
+fn upgrade_weapon_listener(
+    mut upgrade_weapon_event_rx: EventReader<UpgradeWeaponEvent>,
+    mut query: Query<&mut Weapon, With<Ship>>,
+) {
+    for _ in upgrade_weapon_event_rx.read() {
+        if let Ok(mut weapon) = query.get_single_mut() {
+            weapon.level = (weapon.level + 1).min(MAX_WEAPON_LEVEL);
+        }
+    }
+}
+
+/// Updates the ship weapon when its tuning changes
+fn apply_weapon_tuning_system(
+    tuning: Res<WeaponTuning>,
+    asset_server: Res<AssetServer>,
+    mut query: Query<&mut Weapon>,
+) {
+    for mut weapon in &mut query {
+        weapon.apply_tuning(&tuning, &asset_server);
+    }
+}
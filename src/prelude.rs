@@ -1,4 +1,11 @@
-use bevy::{math::bounding::Aabb2d, prelude::*};
+use bevy::prelude::*;
+
+pub use crate::{
+    collision::Collider,
+    enemy::{EnemyDiedEvent, EnemyKind},
+    game_state::{GameState, InGame, NewGameEvent},
+    player::ship::ShipTakeDamageEvent,
+};
 
 /// Provides `XSpeed` to an entity.
 ///
@@ -16,12 +23,6 @@ pub struct YSpeed(pub f32);
 #[derive(Component)]
 pub struct Health(pub f32);
 
-/// Provides `Collider` attribute to an entity.
-///
-/// It will be used to define the bounding box to the entity to allow collision management.
-#[derive(Component)]
-pub struct Collider(pub Aabb2d);
-
 /// Provides `Damage` attribute to an entity.
 #[derive(Component)]
 pub struct Damage(pub f32);
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::game_state::NewGameSet;
+
+/// Duration of a simulation tick, 60 ticks per second.
+pub const SIMULATION_TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Runs the gameplay at a fixed rate, so it behaves the same on every machine.
+///
+/// Gameplay systems live in `FixedUpdate` and move entities in units per second through
+/// `Res<Time>`, which is the fixed clock there. As frames and ticks do not line up, entities
+/// tagged as `Interpolated` are drawn between their last two simulated transforms.
+///
+/// A `HitStopEvent` freezes the simulation for a moment by pausing the virtual clock.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.insert_resource(Time::<Fixed>::from_duration(SIMULATION_TIMESTEP))
+            .init_resource::<HitStop>()
+            .add_event::<HitStopEvent>()
+            .add_systems(PreUpdate, reset_hit_stop_system.in_set(NewGameSet))
+            .add_systems(Update, (end_hit_stop_system, hit_stop_listener).chain())
+            .add_systems(PreUpdate, restore_simulated_transform_system)
+            .add_systems(FixedFirst, save_previous_transform_system)
+            .add_systems(FixedLast, save_simulated_transform_system)
+            .add_systems(
+                PostUpdate,
+                interpolate_transform_system.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Smooths the rendering of an entity moved by the simulation.
+///
+/// Holds the transforms at the end of the last two ticks. The entity `Transform` is only
+/// overwritten for rendering and gets back the simulated value before the next tick.
+#[derive(Component)]
+pub struct Interpolated {
+    previous: Transform,
+    current: Transform,
+}
+
+impl Interpolated {
+    /// Starts interpolating an entity spawned with `transform`.
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            previous: transform,
+            current: transform,
+        }
+    }
+}
+
+/// Puts back the simulated transforms replaced for rendering on the last frame.
+fn restore_simulated_transform_system(mut query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in &mut query {
+        transform.translation = interpolated.current.translation;
+        transform.rotation = interpolated.current.rotation;
+    }
+}
+
+fn save_previous_transform_system(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.previous = *transform;
+    }
+}
+
+fn save_simulated_transform_system(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.current = *transform;
+    }
+}
+
+/// Draws entities between their last two simulated transforms, based on how much time has
+/// passed since the last tick.
+fn interpolate_transform_system(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &Interpolated)>,
+) {
+    let progress = fixed_time.overstep_fraction();
+
+    for (mut transform, interpolated) in &mut query {
+        let Interpolated { previous, current } = interpolated;
+
+        transform.translation = previous.translation.lerp(current.translation, progress);
+        transform.rotation = previous.rotation.slerp(current.rotation, progress);
+    }
+}
+
+/// Sent to freeze the whole game for the given real time, to make an impact stand out.
+#[derive(Event)]
+pub struct HitStopEvent(pub Duration);
+
+/// Real time left before the running hit-stop ends, if any.
+#[derive(Resource, Default)]
+struct HitStop(Option<Timer>);
+
+fn hit_stop_listener(
+    mut hit_stop_event_rx: EventReader<HitStopEvent>,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    for HitStopEvent(duration) in hit_stop_event_rx.read() {
+        if duration.is_zero() {
+            continue;
+        }
+
+        virtual_time.pause();
+        hit_stop.0 = Some(Timer::new(*duration, TimerMode::Once));
+    }
+}
+
+/// Resumes the game once the hit-stop is over. Real time is used as the virtual clock is
+/// paused meanwhile.
+fn end_hit_stop_system(
+    real_time: Res<Time<Real>>,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    let Some(timer) = &mut hit_stop.0 else {
+        return;
+    };
+
+    if timer.tick(real_time.delta()).finished() {
+        virtual_time.unpause();
+        hit_stop.0 = None;
+    }
+}
+
+fn reset_hit_stop_system(mut hit_stop: ResMut<HitStop>, mut virtual_time: ResMut<Time<Virtual>>) {
+    virtual_time.unpause();
+    hit_stop.0 = None;
+}
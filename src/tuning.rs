@@ -0,0 +1,565 @@
+use std::{fmt, sync::OnceLock};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    default_config::{WINDOW_X_LIMIT, WINDOW_Y_LIMIT},
+    enemy::asteroid::AsteroidSize,
+    player::weapon::WeaponKind,
+};
+
+/// Path, relative to the assets folder, of the gameplay tuning file.
+pub const TUNING_PATH: &str = "game.tuning.ron";
+/// Tuning file shipped with the game, used until `TUNING_PATH` is loaded.
+const BUNDLED_TUNING: &str = include_str!("../assets/game.tuning.ron");
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_asset::<Tuning>()
+            .register_asset_loader(TuningLoader)
+            .init_resource::<ShipTuning>()
+            .init_resource::<ShotTuning>()
+            .init_resource::<WeaponTuning>()
+            .init_resource::<AsteroidTuning>()
+            .init_resource::<InvaderTuning>()
+            .init_resource::<EnemyProjectileTuning>()
+            .init_resource::<PowerUpTuning>()
+            .init_resource::<BunkerTuning>()
+            .init_resource::<ComboTuning>()
+            .add_systems(Startup, load_tuning_system)
+            .add_systems(PreUpdate, apply_tuning_listener);
+    }
+}
+
+/// Balance values of the game, loaded from `TUNING_PATH`.
+///
+/// Each section is copied into its own resource once the file is loaded, so gameplay systems
+/// only read the resources and never the asset itself. With the `hot_reload` feature the file
+/// is watched for changes, and every plugin updates its timers and spawned entities when its
+/// section changes.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Tuning {
+    pub ship: ShipTuning,
+    pub shot: ShotTuning,
+    pub weapon: WeaponTuning,
+    pub asteroid: AsteroidTuning,
+    pub invader: InvaderTuning,
+    pub enemy_projectile: EnemyProjectileTuning,
+    pub power_up: PowerUpTuning,
+    pub bunker: BunkerTuning,
+    pub combo: ComboTuning,
+}
+
+/// Player ship stats.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ShipTuning {
+    pub speed: f32,
+    pub collider_size: (f32, f32),
+    /// Seconds the ship ignores any damage after being hit.
+    pub invulnerability_duration: f32,
+    /// Real seconds the whole game freezes when the ship is hit, or `0.0` to never freeze.
+    pub hit_stop_duration: f32,
+    /// Speed the ship is pushed away from what hit it at, fading out quickly.
+    pub knockback_speed: f32,
+}
+
+impl Default for ShipTuning {
+    fn default() -> Self {
+        Tuning::bundled().ship.clone()
+    }
+}
+
+/// Player shots stats shared by every weapon.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ShotTuning {
+    pub collider_size: (f32, f32),
+}
+
+impl Default for ShotTuning {
+    fn default() -> Self {
+        Tuning::bundled().shot.clone()
+    }
+}
+
+/// Player weapons stats, one set per weapon kind.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponTuning {
+    pub single: WeaponKindTuning,
+    pub twin: WeaponKindTuning,
+    pub spread_fan: WeaponKindTuning,
+    pub rapid: WeaponKindTuning,
+    pub piercing: WeaponKindTuning,
+}
+
+/// Stats of a single weapon kind.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponKindTuning {
+    /// Seconds between two volleys.
+    pub cooldown: f32,
+    pub damage: f32,
+    pub speed: f32,
+    /// Path, relative to the assets folder, of the shots image.
+    pub sprite: String,
+}
+
+impl WeaponTuning {
+    /// Gets the stats of the given weapon kind.
+    pub fn kind(&self, kind: WeaponKind) -> &WeaponKindTuning {
+        match kind {
+            WeaponKind::Single => &self.single,
+            WeaponKind::Twin => &self.twin,
+            WeaponKind::SpreadFan => &self.spread_fan,
+            WeaponKind::Rapid => &self.rapid,
+            WeaponKind::Piercing => &self.piercing,
+        }
+    }
+}
+
+impl Default for WeaponTuning {
+    fn default() -> Self {
+        Tuning::bundled().weapon.clone()
+    }
+}
+
+/// Asteroids stats, one set per size tier.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AsteroidTuning {
+    pub big: AsteroidTierTuning,
+    pub medium: AsteroidTierTuning,
+    pub small: AsteroidTierTuning,
+    /// Horizontal speed of the outermost fragments of a destroyed asteroid.
+    pub split_speed: f32,
+    /// Radius of the round collider of the unscaled asteroid sprite, which smaller tiers
+    /// scale down along with the sprite.
+    pub collider_radius: f32,
+}
+
+/// Stats of a single asteroid size tier.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AsteroidTierTuning {
+    pub speed: f32,
+    pub health: f32,
+}
+
+impl AsteroidTuning {
+    /// Gets the stats of the given asteroid size.
+    pub fn tier(&self, size: AsteroidSize) -> &AsteroidTierTuning {
+        match size {
+            AsteroidSize::Big => &self.big,
+            AsteroidSize::Medium => &self.medium,
+            AsteroidSize::Small => &self.small,
+        }
+    }
+}
+
+impl Default for AsteroidTuning {
+    fn default() -> Self {
+        Tuning::bundled().asteroid.clone()
+    }
+}
+
+/// Invaders formation stats.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct InvaderTuning {
+    pub health: f32,
+    /// Seconds between formation steps while every member is alive.
+    pub step_interval: f32,
+    /// Seconds between formation steps once most members are dead.
+    pub min_step_interval: f32,
+    pub step_x: f32,
+    pub step_y: f32,
+    pub collider_size: (f32, f32),
+}
+
+impl Default for InvaderTuning {
+    fn default() -> Self {
+        Tuning::bundled().invader.clone()
+    }
+}
+
+/// Stats of the projectiles fired by enemies.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyProjectileTuning {
+    pub speed: f32,
+    pub damage: f32,
+    pub collider_size: (f32, f32),
+}
+
+impl Default for EnemyProjectileTuning {
+    fn default() -> Self {
+        Tuning::bundled().enemy_projectile.clone()
+    }
+}
+
+/// Stats of the power-ups dropped by enemies.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PowerUpTuning {
+    pub fall_speed: f32,
+    /// Seconds a timed power-up lasts once collected.
+    pub buff_duration: f32,
+    pub collider_size: (f32, f32),
+}
+
+impl Default for PowerUpTuning {
+    fn default() -> Self {
+        Tuning::bundled().power_up.clone()
+    }
+}
+
+/// Layout and stats of the bunkers shielding the ship.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BunkerTuning {
+    /// Bunkers evenly spread across the screen, or `0` to play without them.
+    pub count: u32,
+    /// Cells making up the width of a bunker.
+    pub columns: u32,
+    /// Cells making up the height of a bunker.
+    pub rows: u32,
+    /// Side of the square cells.
+    pub cell_size: f32,
+    pub cell_health: f32,
+    /// Height of the center of the bunkers.
+    pub y_position: f32,
+}
+
+impl Default for BunkerTuning {
+    fn default() -> Self {
+        Tuning::bundled().bunker.clone()
+    }
+}
+
+/// Score multiplier and bonus points rewarding skilled play.
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ComboTuning {
+    /// Seconds after a kill during which the next kill keeps the chain going.
+    pub window: f32,
+    /// Kills in a row needed to raise the multiplier by one.
+    pub kills_per_level: u32,
+    pub max_multiplier: u32,
+    /// Shortest chain which earns `chain_bonus` points per kill once it ends.
+    pub min_chain: u32,
+    pub chain_bonus: u32,
+    /// Points for clearing a wave without being hit nor letting an enemy escape.
+    pub no_miss_bonus: u32,
+    /// Points for clearing a wave with every shot hitting, reduced by the shots which missed.
+    pub accuracy_bonus: u32,
+}
+
+impl Default for ComboTuning {
+    fn default() -> Self {
+        Tuning::bundled().combo.clone()
+    }
+}
+
+/// Names a `Tuning` field by its path from the root of the file, such as `ship.speed`.
+macro_rules! field_path {
+    ($first:ident $(. $rest:ident)*) => {
+        concat!(stringify!($first) $(, ".", stringify!($rest))*)
+    };
+}
+
+/// Runs `$ensure` on a field read from `$root`, naming the field in the error by the same path
+/// it is read through.
+macro_rules! check {
+    ($ensure:ident, $root:ident $(. $field:ident)+) => {
+        $ensure(field_path!($($field).+), $root $(.$field)+)?
+    };
+}
+
+impl Tuning {
+    /// Gets the tuning file shipped with the game, which all the section defaults come from.
+    pub fn bundled() -> &'static Tuning {
+        static BUNDLED: OnceLock<Tuning> = OnceLock::new();
+
+        BUNDLED.get_or_init(|| {
+            ron::de::from_str(BUNDLED_TUNING).expect("the bundled tuning file should be valid")
+        })
+    }
+
+    /// Checks every value is usable by the game, naming the first invalid field otherwise.
+    pub fn validate(&self) -> Result<(), TuningLoaderError> {
+        check!(ensure_positive, self.ship.speed);
+        check!(ensure_positive_size, self.ship.collider_size);
+        check!(ensure_positive, self.ship.invulnerability_duration);
+        check!(ensure_non_negative, self.ship.hit_stop_duration);
+        check!(ensure_non_negative, self.ship.knockback_speed);
+
+        check!(ensure_positive_size, self.shot.collider_size);
+
+        check!(ensure_positive, self.weapon.single.cooldown);
+        check!(ensure_positive, self.weapon.single.damage);
+        check!(ensure_positive, self.weapon.single.speed);
+        check!(ensure_positive, self.weapon.twin.cooldown);
+        check!(ensure_positive, self.weapon.twin.damage);
+        check!(ensure_positive, self.weapon.twin.speed);
+        check!(ensure_positive, self.weapon.spread_fan.cooldown);
+        check!(ensure_positive, self.weapon.spread_fan.damage);
+        check!(ensure_positive, self.weapon.spread_fan.speed);
+        check!(ensure_positive, self.weapon.rapid.cooldown);
+        check!(ensure_positive, self.weapon.rapid.damage);
+        check!(ensure_positive, self.weapon.rapid.speed);
+        check!(ensure_positive, self.weapon.piercing.cooldown);
+        check!(ensure_positive, self.weapon.piercing.damage);
+        check!(ensure_positive, self.weapon.piercing.speed);
+
+        check!(ensure_positive, self.asteroid.big.speed);
+        check!(ensure_positive, self.asteroid.big.health);
+        check!(ensure_positive, self.asteroid.medium.speed);
+        check!(ensure_positive, self.asteroid.medium.health);
+        check!(ensure_positive, self.asteroid.small.speed);
+        check!(ensure_positive, self.asteroid.small.health);
+        check!(ensure_positive, self.asteroid.split_speed);
+        check!(ensure_positive, self.asteroid.collider_radius);
+
+        check!(ensure_positive, self.invader.health);
+        check!(ensure_positive, self.invader.step_interval);
+        check!(ensure_positive, self.invader.min_step_interval);
+        check!(ensure_positive, self.invader.step_x);
+        check!(ensure_positive, self.invader.step_y);
+        check!(ensure_positive_size, self.invader.collider_size);
+
+        if self.invader.min_step_interval > self.invader.step_interval {
+            return Err(TuningLoaderError::Invalid {
+                field: field_path!(invader.min_step_interval),
+                reason: "expected to be lower than `invader.step_interval`".into(),
+            });
+        }
+
+        check!(ensure_positive, self.enemy_projectile.speed);
+        check!(ensure_positive, self.enemy_projectile.damage);
+        check!(ensure_positive_size, self.enemy_projectile.collider_size);
+
+        check!(ensure_positive, self.power_up.fall_speed);
+        check!(ensure_positive, self.power_up.buff_duration);
+        check!(ensure_positive_size, self.power_up.collider_size);
+
+        check!(ensure_positive, self.bunker.cell_size);
+        check!(ensure_positive, self.bunker.cell_health);
+
+        let bunkers_width =
+            self.bunker.count as f32 * self.bunker.columns as f32 * self.bunker.cell_size;
+
+        if bunkers_width > 2.0 * WINDOW_X_LIMIT {
+            return Err(TuningLoaderError::Invalid {
+                field: field_path!(bunker.count),
+                reason: format!("{} bunkers do not fit in the screen", self.bunker.count),
+            });
+        }
+
+        if self.bunker.y_position.is_nan() || self.bunker.y_position.abs() > WINDOW_Y_LIMIT {
+            return Err(TuningLoaderError::Invalid {
+                field: field_path!(bunker.y_position),
+                reason: format!(
+                    "expected a height between -{WINDOW_Y_LIMIT} and {WINDOW_Y_LIMIT}, got {}",
+                    self.bunker.y_position
+                ),
+            });
+        }
+
+        check!(ensure_positive, self.combo.window);
+        check!(ensure_at_least_one, self.combo.kills_per_level);
+        check!(ensure_at_least_one, self.combo.max_multiplier);
+        check!(ensure_at_least_one, self.combo.min_chain);
+
+        Ok(())
+    }
+}
+
+fn ensure_positive(field: &'static str, value: f32) -> Result<(), TuningLoaderError> {
+    // Written this way so `NaN` is rejected too
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(TuningLoaderError::Invalid {
+            field,
+            reason: format!("expected a positive number, got {value}"),
+        })
+    }
+}
+
+fn ensure_non_negative(field: &'static str, value: f32) -> Result<(), TuningLoaderError> {
+    if value >= 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(TuningLoaderError::Invalid {
+            field,
+            reason: format!("expected zero or a positive number, got {value}"),
+        })
+    }
+}
+
+fn ensure_at_least_one(field: &'static str, value: u32) -> Result<(), TuningLoaderError> {
+    if value >= 1 {
+        Ok(())
+    } else {
+        Err(TuningLoaderError::Invalid {
+            field,
+            reason: "expected at least 1".into(),
+        })
+    }
+}
+
+fn ensure_positive_size(field: &'static str, size: (f32, f32)) -> Result<(), TuningLoaderError> {
+    let (width, height) = size;
+
+    if width > 0.0 && height > 0.0 && width.is_finite() && height.is_finite() {
+        Ok(())
+    } else {
+        Err(TuningLoaderError::Invalid {
+            field,
+            reason: format!("expected a positive (width, height), got ({width}, {height})"),
+        })
+    }
+}
+
+/// Errors raised while loading a `Tuning` file.
+#[derive(Debug)]
+pub enum TuningLoaderError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    /// The file is well formed but `field` holds a value the game cannot use.
+    Invalid {
+        field: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for TuningLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TuningLoaderError::Io(error) => write!(f, "could not read tuning file: {error}"),
+            TuningLoaderError::Parse(error) => write!(f, "could not parse tuning file: {error}"),
+            TuningLoaderError::Invalid { field, reason } => {
+                write!(f, "invalid tuning field `{field}`: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TuningLoaderError {}
+
+impl From<std::io::Error> for TuningLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        TuningLoaderError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for TuningLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        TuningLoaderError::Parse(error)
+    }
+}
+
+/// Loads and validates `.tuning.ron` files.
+#[derive(Default)]
+pub struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = TuningLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let tuning = ron::de::from_bytes::<Tuning>(&bytes)?;
+        tuning.validate()?;
+
+        Ok(tuning)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+/// Keeps the tuning file loaded for the whole lifetime of the app.
+#[derive(Resource)]
+pub struct TuningHandle(pub Handle<Tuning>);
+
+/// Starts loading the tuning file. Defaults are used until it is ready.
+fn load_tuning_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+/// Copies the tuning file sections into their resources once it is loaded or edited.
+fn apply_tuning_listener(
+    mut tuning_event_rx: EventReader<AssetEvent<Tuning>>,
+    tunings: Res<Assets<Tuning>>,
+    mut commands: Commands,
+) {
+    for event in tuning_event_rx.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if let Some(tuning) = tunings.get(*id) {
+                info!("Applying gameplay tuning from {TUNING_PATH}");
+
+                commands.insert_resource(tuning.ship.clone());
+                commands.insert_resource(tuning.shot.clone());
+                commands.insert_resource(tuning.weapon.clone());
+                commands.insert_resource(tuning.asteroid.clone());
+                commands.insert_resource(tuning.invader.clone());
+                commands.insert_resource(tuning.enemy_projectile.clone());
+                commands.insert_resource(tuning.power_up.clone());
+                commands.insert_resource(tuning.bunker.clone());
+                commands.insert_resource(tuning.combo.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejected_field(tuning: &Tuning) -> Option<&'static str> {
+        match tuning.validate() {
+            Err(TuningLoaderError::Invalid { field, .. }) => Some(field),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn bundled_tuning_is_valid() {
+        assert!(Tuning::bundled().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_non_positive_field_by_name() {
+        let mut tuning = Tuning::bundled().clone();
+        tuning.weapon.spread_fan.speed = 0.0;
+
+        assert_eq!(rejected_field(&tuning), Some("weapon.spread_fan.speed"));
+    }
+
+    #[test]
+    fn rejects_min_step_interval_above_step_interval() {
+        let mut tuning = Tuning::bundled().clone();
+        tuning.invader.min_step_interval = tuning.invader.step_interval + 0.1;
+
+        assert_eq!(rejected_field(&tuning), Some("invader.min_step_interval"));
+    }
+}
@@ -0,0 +1,97 @@
+use bevy::{
+    app::{Plugin, PreUpdate, Update},
+    math::vec3,
+    prelude::{
+        in_state, Bundle, Commands, Component, IntoSystemConfigs, Query, Res, StateScoped,
+        Transform, With,
+    },
+    sprite::Anchor,
+    text::{Text, Text2dBundle, TextSection, TextStyle},
+};
+
+use crate::{
+    default_config::{HUD_X_LIMIT, WINDOW_Y_LIMIT, WINDOW_Y_PADDING},
+    game_state::{despawn_all_system, GameState, InGame, NewGameSet},
+    player::powerup::{ActiveBuffs, PowerUpKind},
+};
+
+const BUFFS_FONT_SIZE: f32 = 18.0;
+/// Gap between the score counter, best score included, and the first buff countdown.
+const BUFFS_TOP_GAP: f32 = 45.0;
+
+pub struct BuffsHudPlugin;
+
+impl Plugin for BuffsHudPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_systems(
+            PreUpdate,
+            (despawn_all_system::<BuffsHud>, create_buffs_hud_system)
+                .chain()
+                .in_set(NewGameSet),
+        )
+        .add_systems(
+            Update,
+            update_buffs_hud_system.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Tags the HUD text listing the active buffs with their countdown.
+#[derive(Component)]
+pub struct BuffsHud;
+
+#[derive(Bundle)]
+struct BuffsHudBundle {
+    text: Text2dBundle,
+    buffs_hud: BuffsHud,
+}
+
+impl BuffsHudBundle {
+    fn new() -> Self {
+        BuffsHudBundle {
+            text: Text2dBundle {
+                text: Text::default(),
+                // Lines grow downwards from below the score counter
+                text_anchor: Anchor::TopLeft,
+                transform: Transform {
+                    translation: vec3(
+                        -HUD_X_LIMIT,
+                        WINDOW_Y_LIMIT - WINDOW_Y_PADDING - BUFFS_TOP_GAP,
+                        0.0,
+                    ),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            buffs_hud: BuffsHud,
+        }
+    }
+}
+
+/// Spawns the buffs countdown in the screen
+fn create_buffs_hud_system(mut commands: Commands) {
+    commands.spawn((BuffsHudBundle::new(), StateScoped(InGame)));
+}
+
+/// Shows the seconds left of every active buff, one per line
+fn update_buffs_hud_system(
+    buffs: Res<ActiveBuffs>,
+    mut buffs_hud_query: Query<&mut Text, With<BuffsHud>>,
+) {
+    let Ok(mut buffs_text) = buffs_hud_query.get_single_mut() else {
+        return;
+    };
+
+    buffs_text.sections = PowerUpKind::TIMED
+        .iter()
+        .filter_map(|kind| {
+            buffs.remaining_secs(*kind).map(|remaining| TextSection {
+                value: format!("{} {:.1}\n", kind.label(), remaining),
+                style: TextStyle {
+                    font_size: BUFFS_FONT_SIZE,
+                    ..Default::default()
+                },
+            })
+        })
+        .collect();
+}
@@ -0,0 +1,337 @@
+use std::collections::HashSet;
+
+use bevy::{math::vec3, prelude::*, sprite::Anchor};
+
+use crate::{
+    collision::CollisionStartedEvent,
+    default_config::{HUD_X_LIMIT, WINDOW_Y_LIMIT, WINDOW_Y_PADDING},
+    enemy::{
+        check_enemy_died, enemy_take_damage_listener, escaped_enemies_listener,
+        wave::{WaveClearedEvent, WaveStartedEvent},
+        Enemy, EnemyDiedEvent, EnemyEscapedEvent,
+    },
+    game_state::{despawn_all_system, GameState, InGame, NewGameSet},
+    player::{ship::ShipTakeDamageEvent, shot::Shot},
+    tuning::ComboTuning,
+};
+
+use super::score::Score;
+
+const COMBO_FONT_SIZE: f32 = 18.0;
+/// Gap between the hearts and the combo decay bar.
+const COMBO_TOP_GAP: f32 = 25.0;
+const COMBO_BAR_SIZE: Vec2 = Vec2::new(80.0, 4.0);
+/// Gap between the decay bar and the combo text below it.
+const COMBO_TEXT_GAP: f32 = 8.0;
+const COMBO_BAR_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+/// Seconds the last bonus stays on the HUD.
+const BONUS_DISPLAY_DURATION: f32 = 2.0;
+
+/// Scores kills through a multiplier which grows with kills chained in quick succession, and
+/// grants bonus points for chains, no-miss waves and accurate waves.
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Combo>()
+            .init_resource::<WaveStats>()
+            .add_event::<ScoreBonusEvent>()
+            .add_systems(
+                PreUpdate,
+                (
+                    reset_combo_system,
+                    (despawn_all_system::<ComboHud>, create_combo_hud_system).chain(),
+                )
+                    .in_set(NewGameSet),
+            )
+            .add_systems(
+                FixedUpdate,
+                // Shots are despawned as soon as they hit
+                wave_stats_system
+                    .before(enemy_take_damage_listener)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                (combo_listener, combo_window_system, wave_bonus_listener)
+                    .chain()
+                    .after(check_enemy_died)
+                    .after(escaped_enemies_listener)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                update_combo_hud_system.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Chain of kills, each one scored before the previous one's window ran out.
+#[derive(Resource, Default)]
+pub struct Combo {
+    kills: u32,
+    window: Timer,
+}
+
+impl Combo {
+    pub fn kills(&self) -> u32 {
+        self.kills
+    }
+
+    /// Gets the factor applied to the points of the next kills.
+    pub fn multiplier(&self, tuning: &ComboTuning) -> u32 {
+        (1 + self.kills.saturating_sub(1) / tuning.kills_per_level).min(tuning.max_multiplier)
+    }
+
+    /// Gets the fraction of the window left to keep the chain going, `0.0` without a chain.
+    pub fn remaining_fraction(&self) -> f32 {
+        if self.kills == 0 {
+            0.0
+        } else {
+            self.window.fraction_remaining()
+        }
+    }
+}
+
+/// Ways to earn points on top of the kills.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreBonus {
+    /// A chain of kills ended without the ship being hit.
+    Chain,
+    /// A wave was cleared without the ship being hit nor any enemy escaping.
+    NoMiss,
+    /// A wave was cleared, scaled by the fraction of shots which hit an enemy.
+    Accuracy,
+}
+
+impl ScoreBonus {
+    /// Gets the name shown on the HUD.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScoreBonus::Chain => "CHAIN",
+            ScoreBonus::NoMiss => "NO MISS",
+            ScoreBonus::Accuracy => "ACCURACY",
+        }
+    }
+}
+
+/// Sent when bonus points are added to the `Score`.
+#[derive(Event)]
+pub struct ScoreBonusEvent(pub ScoreBonus, pub u32);
+
+/// What happened during the current wave, for the wave bonuses.
+#[derive(Resource, Default)]
+struct WaveStats {
+    shots_fired: u32,
+    /// Shots which hit at least one enemy.
+    hitting_shots: HashSet<Entity>,
+    /// Whether the ship was hit or an enemy escaped.
+    missed: bool,
+}
+
+fn reset_combo_system(mut combo: ResMut<Combo>, mut wave_stats: ResMut<WaveStats>) {
+    *combo = Combo::default();
+    *wave_stats = WaveStats::default();
+}
+
+/// Counts the shots fired and the ones hitting an enemy, and notes any miss of the wave.
+#[allow(clippy::too_many_arguments)]
+fn wave_stats_system(
+    mut wave_started_event_rx: EventReader<WaveStartedEvent>,
+    mut collision_started_event_rx: EventReader<CollisionStartedEvent>,
+    mut ship_take_damage_event_rx: EventReader<ShipTakeDamageEvent>,
+    mut enemy_escaped_event_rx: EventReader<EnemyEscapedEvent>,
+    new_shots_query: Query<(), Added<Shot>>,
+    shots_query: Query<(), With<Shot>>,
+    enemies_query: Query<(), With<Enemy>>,
+    mut wave_stats: ResMut<WaveStats>,
+) {
+    if wave_started_event_rx.read().count() > 0 {
+        *wave_stats = WaveStats::default();
+    }
+
+    wave_stats.shots_fired += new_shots_query.iter().count() as u32;
+
+    for CollisionStartedEvent(shot_entity, enemy_entity) in collision_started_event_rx.read() {
+        if shots_query.contains(*shot_entity) && enemies_query.contains(*enemy_entity) {
+            wave_stats.hitting_shots.insert(*shot_entity);
+        }
+    }
+
+    if ship_take_damage_event_rx.read().count() > 0 || enemy_escaped_event_rx.read().count() > 0 {
+        wave_stats.missed = true;
+    }
+}
+
+/// Scores every kill with the current multiplier and extends the chain, which the ship
+/// loses when it is hit.
+fn combo_listener(
+    mut enemy_died_event_rx: EventReader<EnemyDiedEvent>,
+    mut ship_take_damage_event_rx: EventReader<ShipTakeDamageEvent>,
+    tuning: Res<ComboTuning>,
+    mut combo: ResMut<Combo>,
+    mut score: ResMut<Score>,
+) {
+    if ship_take_damage_event_rx.read().count() > 0 {
+        *combo = Combo::default();
+    }
+
+    for EnemyDiedEvent(kind, _) in enemy_died_event_rx.read() {
+        combo.kills += 1;
+        combo.window = Timer::from_seconds(tuning.window, TimerMode::Once);
+        score.0 += kind.score() * combo.multiplier(&tuning);
+    }
+}
+
+/// Ends the chain once its window runs out, granting the chain bonus if it was long enough.
+fn combo_window_system(
+    time: Res<Time>,
+    tuning: Res<ComboTuning>,
+    mut combo: ResMut<Combo>,
+    mut score: ResMut<Score>,
+    mut score_bonus_event_tx: EventWriter<ScoreBonusEvent>,
+) {
+    if combo.kills == 0 || !combo.window.tick(time.delta()).finished() {
+        return;
+    }
+
+    if combo.kills >= tuning.min_chain {
+        let bonus = combo.kills * tuning.chain_bonus;
+
+        score.0 += bonus;
+        score_bonus_event_tx.send(ScoreBonusEvent(ScoreBonus::Chain, bonus));
+    }
+
+    *combo = Combo::default();
+}
+
+/// Grants the no-miss and accuracy bonuses when a wave is cleared.
+fn wave_bonus_listener(
+    mut wave_cleared_event_rx: EventReader<WaveClearedEvent>,
+    tuning: Res<ComboTuning>,
+    wave_stats: Res<WaveStats>,
+    mut score: ResMut<Score>,
+    mut score_bonus_event_tx: EventWriter<ScoreBonusEvent>,
+) {
+    for _ in wave_cleared_event_rx.read() {
+        let mut bonuses = Vec::new();
+
+        if !wave_stats.missed {
+            bonuses.push((ScoreBonus::NoMiss, tuning.no_miss_bonus));
+        }
+
+        if wave_stats.shots_fired > 0 {
+            // Shots fired before the wave started may hit during it
+            let accuracy =
+                (wave_stats.hitting_shots.len() as f32 / wave_stats.shots_fired as f32).min(1.0);
+
+            bonuses.push((
+                ScoreBonus::Accuracy,
+                (tuning.accuracy_bonus as f32 * accuracy).round() as u32,
+            ));
+        }
+
+        for (bonus, points) in bonuses {
+            if points > 0 {
+                score.0 += points;
+                score_bonus_event_tx.send(ScoreBonusEvent(bonus, points));
+            }
+        }
+    }
+}
+
+/// Tags the HUD elements showing the `Combo`.
+#[derive(Component)]
+struct ComboHud;
+
+/// Tags the bar shrinking as the combo window runs out.
+#[derive(Component)]
+struct ComboBar;
+
+/// Spawns the combo decay bar in the screen below the hearts, with the multiplier under it
+fn create_combo_hud_system(mut commands: Commands) {
+    let right_x = HUD_X_LIMIT;
+    let top_y = WINDOW_Y_LIMIT - WINDOW_Y_PADDING - COMBO_TOP_GAP;
+
+    commands.spawn((
+        Text2dBundle {
+            // Lines grow downwards, right aligned with the hearts
+            text_anchor: Anchor::TopRight,
+            text: Text::default().with_justify(JustifyText::Right),
+            transform: Transform::from_translation(vec3(right_x, top_y - COMBO_TEXT_GAP, 0.0)),
+            ..Default::default()
+        },
+        ComboHud,
+        StateScoped(InGame),
+    ));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: COMBO_BAR_COLOR,
+                custom_size: Some(COMBO_BAR_SIZE),
+                // Shrinks towards the right edge
+                anchor: Anchor::CenterRight,
+                ..Default::default()
+            },
+            transform: Transform::from_translation(vec3(right_x, top_y, 0.0)),
+            ..Default::default()
+        },
+        ComboBar,
+        ComboHud,
+        StateScoped(InGame),
+    ));
+}
+
+/// Shows the multiplier and the chain length while a chain is going, the time left to extend
+/// it, and the last bonus earned for a moment
+fn update_combo_hud_system(
+    mut score_bonus_event_rx: EventReader<ScoreBonusEvent>,
+    time: Res<Time>,
+    combo: Res<Combo>,
+    tuning: Res<ComboTuning>,
+    mut last_bonus: Local<Option<(ScoreBonus, u32, Timer)>>,
+    mut combo_text_query: Query<&mut Text, (With<ComboHud>, Without<ComboBar>)>,
+    mut combo_bar_query: Query<&mut Sprite, With<ComboBar>>,
+) {
+    for ScoreBonusEvent(bonus, points) in score_bonus_event_rx.read() {
+        *last_bonus = Some((
+            *bonus,
+            *points,
+            Timer::from_seconds(BONUS_DISPLAY_DURATION, TimerMode::Once),
+        ));
+    }
+
+    if let Some((_, _, timer)) = last_bonus.as_mut() {
+        if timer.tick(time.delta()).finished() {
+            *last_bonus = None;
+        }
+    }
+
+    let style = TextStyle {
+        font_size: COMBO_FONT_SIZE,
+        ..Default::default()
+    };
+    let mut sections = Vec::new();
+
+    if combo.kills() > 0 {
+        sections.push(TextSection::new(
+            format!("x{} CHAIN {}\n", combo.multiplier(&tuning), combo.kills()),
+            style.clone(),
+        ));
+    }
+
+    if let Some((bonus, points, _)) = last_bonus.as_ref() {
+        sections.push(TextSection::new(
+            format!("{} +{points}\n", bonus.label()),
+            style,
+        ));
+    }
+
+    if let Ok(mut combo_text) = combo_text_query.get_single_mut() {
+        combo_text.sections = sections;
+    }
+
+    if let Ok(mut combo_bar) = combo_bar_query.get_single_mut() {
+        combo_bar.custom_size = Some(COMBO_BAR_SIZE * Vec2::new(combo.remaining_fraction(), 1.0));
+    }
+}
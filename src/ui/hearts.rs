@@ -1,36 +1,37 @@
 use bevy::{
-    app::{Plugin, PostUpdate, Startup},
+    app::{Plugin, Update},
     asset::{AssetServer, Handle},
     math::vec3,
     prelude::{
-        Bundle, Commands, Component, Entity, EventReader, Image, Query, Res, Transform, With,
+        in_state, resource_changed, Bundle, Commands, Component, Condition, Entity, Image,
+        IntoSystemConfigs, Query, Res, StateScoped, Transform, With,
     },
     sprite::SpriteBundle,
 };
 
 use crate::{
-    default_config::{WINDOW_X_LIMIT, WINDOW_Y_LIMIT, WINDOW_Y_PADDING},
-    player::ship::ShipTakeDamageEvent,
+    default_config::{HUD_X_LIMIT, WINDOW_Y_LIMIT, WINDOW_Y_PADDING},
+    game_state::InGame,
+    player::lives::Lives,
 };
 
-// TODO: check how hell are hearts rendering?
-// I've done some calculations and added some gap and positioning but really
-// I'm not sure how it is working
-const USER_LIVES_AMOUNT: i8 = 3;
+/// Distance between the centers of two hearts.
 const HEARTS_GAP: f32 = 35.0;
+/// Center of the rightmost heart, the others being laid out leftwards.
+const HEARTS_RIGHT_X: f32 = HUD_X_LIMIT;
 
 pub struct HeartsPlugin;
 
 impl Plugin for HeartsPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(Startup, create_lives_system)
-            .add_systems(PostUpdate, decrease_life_system);
+        app.add_systems(
+            Update,
+            render_hearts_system.run_if(in_state(InGame).and_then(resource_changed::<Lives>)),
+        );
     }
 }
 
-/// Provides the `Heart` attribute
-///
-/// It represents the user's health
+/// Tags one of the hearts showing the player `Lives`
 #[derive(Component)]
 struct Heart;
 
@@ -47,11 +48,7 @@ impl HeartBundle {
             sprite: SpriteBundle {
                 texture,
                 transform: Transform {
-                    translation: vec3(
-                        WINDOW_X_LIMIT + x_position - HEARTS_GAP,
-                        WINDOW_Y_LIMIT - WINDOW_Y_PADDING,
-                        0.0,
-                    ),
+                    translation: vec3(x_position, WINDOW_Y_LIMIT - WINDOW_Y_PADDING, 0.0),
                     ..Default::default()
                 },
                 ..Default::default()
@@ -61,36 +58,28 @@ impl HeartBundle {
     }
 }
 
-/// Spawns the lives of the user and renders in screen using hearts
-fn create_lives_system(mut commands: Commands, asset_server: Res<AssetServer>) {
-    // Need to iterate from one based values to give correct values
-    // to hearts and position for sprites
-    for value in 0..USER_LIVES_AMOUNT {
-        let heart_texture: Handle<Image> = asset_server.load("player_heart.png");
-
-        commands.spawn(HeartBundle::new(
-            HEARTS_GAP * f32::from(value),
-            heart_texture,
-        ));
-    }
-}
-
-/// Reduces player's lives by one
-fn decrease_life_system(
-    lives_query: Query<Entity, With<Heart>>,
-    mut ship_take_damage_event_rx: EventReader<ShipTakeDamageEvent>,
+/// Shows one heart per life left, right aligned in the top of the screen
+///
+/// Hearts are spawned again from scratch whenever `Lives` changes.
+fn render_hearts_system(
+    lives: Res<Lives>,
+    hearts_query: Query<Entity, With<Heart>>,
+    asset_server: Res<AssetServer>,
     mut commands: Commands,
 ) {
-    for event in ship_take_damage_event_rx.read() {
-        // Despawn the collided enemy
-        commands.entity(event.0).despawn();
+    for heart_entity in &hearts_query {
+        commands.entity(heart_entity).despawn();
+    }
 
-        // Skip one heart bundle because we want make player lose on 3 collisions
-        if let Some(heart_entity) = lives_query.iter().skip(1).next() {
-            // Despawn heart
-            commands.entity(heart_entity).despawn();
-        } else {
-            panic!("Implement losing game state")
-        }
+    let heart_texture: Handle<Image> = asset_server.load("player_heart.png");
+
+    for index in 0..lives.get() {
+        commands.spawn((
+            HeartBundle::new(
+                HEARTS_RIGHT_X - HEARTS_GAP * index as f32,
+                heart_texture.clone(),
+            ),
+            StateScoped(InGame),
+        ));
     }
 }
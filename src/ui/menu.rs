@@ -0,0 +1,286 @@
+use bevy::{
+    app::{Plugin, Update},
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonInput, ButtonState,
+    },
+    log::warn,
+    prelude::{
+        in_state, not, Commands, Component, EventReader, EventWriter, Events, IntoSystemConfigs,
+        KeyCode, NextState, OnEnter, Query, Res, ResMut, Resource, StateScoped, Transform, With,
+    },
+    text::{JustifyText, Text, Text2dBundle, TextSection, TextStyle},
+};
+
+use crate::{
+    game_state::{GameState, NewGameEvent},
+    high_scores::{sanitize_initials, HighScores, MAX_INITIALS_LENGTH},
+    player::shot::FireMode,
+};
+
+use super::score::Score;
+
+const MENU_FONT_SIZE: f32 = 32.0;
+const SETTINGS_FONT_SIZE: f32 = 20.0;
+/// Distance between the center of the screen and the settings shown below the main menu.
+const SETTINGS_Y_OFFSET: f32 = 120.0;
+const CHANGE_FIRE_MODE_KEY: KeyCode = KeyCode::KeyF;
+const SETTINGS_KEY: KeyCode = KeyCode::KeyS;
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<InitialsEntry>()
+            .add_systems(OnEnter(GameState::MainMenu), create_main_menu_system)
+            .add_systems(OnEnter(GameState::Paused), create_pause_menu_system)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (clear_keyboard_input_system, create_game_over_menu_system),
+            )
+            .add_systems(
+                Update,
+                (
+                    (start_game_listener, change_fire_mode_listener)
+                        .run_if(in_state(GameState::MainMenu)),
+                    restart_game_listener.run_if(in_state(GameState::Paused)),
+                    // The key confirming the initials must not dismiss the screen too
+                    (
+                        game_over_listener.run_if(not(is_entering_initials)),
+                        enter_initials_listener.run_if(is_entering_initials),
+                    )
+                        .chain()
+                        .run_if(in_state(GameState::GameOver)),
+                ),
+            );
+    }
+}
+
+/// Builds a centered text screen which only lives while the game is in `state`.
+fn menu_text(value: &str, state: GameState) -> (Text2dBundle, StateScoped<GameState>) {
+    let text = Text2dBundle {
+        text: Text::from_section(
+            value,
+            TextStyle {
+                font_size: MENU_FONT_SIZE,
+                ..Default::default()
+            },
+        )
+        .with_justify(JustifyText::Center),
+        ..Default::default()
+    };
+
+    (text, StateScoped(state))
+}
+
+/// Tags the main menu text showing the selected `FireMode`.
+#[derive(Component)]
+struct FireModeText;
+
+fn fire_mode_section(fire_mode: FireMode) -> TextSection {
+    TextSection {
+        value: format!("Press F to change fire mode: {}", fire_mode.label()),
+        style: TextStyle {
+            font_size: SETTINGS_FONT_SIZE,
+            ..Default::default()
+        },
+    }
+}
+
+/// Renders the main menu
+fn create_main_menu_system(mut commands: Commands, fire_mode: Res<FireMode>) {
+    commands.spawn(menu_text(
+        "RUSTY SPACE INVADERS\n\nPress ENTER to start\nPress S for settings",
+        GameState::MainMenu,
+    ));
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections([fire_mode_section(*fire_mode)]),
+            transform: Transform::from_xyz(0.0, -SETTINGS_Y_OFFSET, 0.0),
+            ..Default::default()
+        },
+        FireModeText,
+        StateScoped(GameState::MainMenu),
+    ));
+}
+
+/// Renders the pause menu on top of the frozen run
+fn create_pause_menu_system(mut commands: Commands) {
+    commands.spawn(menu_text(
+        "PAUSED\n\nPress ESC to resume\nPress R to restart",
+        GameState::Paused,
+    ));
+}
+
+/// Initials typed by the player on the game over screen, while the run is waiting to get into
+/// the `HighScores`.
+#[derive(Resource, Default)]
+struct InitialsEntry(Option<String>);
+
+fn is_entering_initials(initials_entry: Res<InitialsEntry>) -> bool {
+    initials_entry.0.is_some()
+}
+
+/// Tags the game over screen text, which changes once the initials are entered.
+#[derive(Component)]
+struct GameOverText;
+
+fn initials_entry_value(initials: &str, score: u32) -> String {
+    let blanks = "_".repeat(MAX_INITIALS_LENGTH - initials.len());
+
+    format!(
+        "NEW HIGH SCORE: {score}\n\nType your initials\n{initials}{blanks}\n\n\
+        Press ENTER to confirm"
+    )
+}
+
+fn game_over_value(high_scores: &HighScores, score: u32) -> String {
+    let table: String = high_scores
+        .entries()
+        .iter()
+        .enumerate()
+        .map(|(index, high_score)| {
+            format!(
+                "{}. {} {}\n",
+                index + 1,
+                high_score.initials,
+                high_score.score
+            )
+        })
+        .collect();
+
+    format!(
+        "GAME OVER\n\nSCORE: {score}\n\n{table}\n\
+        Press ENTER to play again\nPress ESC for main menu"
+    )
+}
+
+/// Drops the keys pressed during the last frames of the run, such as the movement ones, so
+/// they do not get typed into the initials
+fn clear_keyboard_input_system(mut keyboard_input_events: ResMut<Events<KeyboardInput>>) {
+    keyboard_input_events.clear();
+}
+
+/// Renders the game over screen, asking for the player initials first if the run earned a
+/// high score
+fn create_game_over_menu_system(
+    mut commands: Commands,
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    mut initials_entry: ResMut<InitialsEntry>,
+) {
+    let value = if high_scores.qualifies(score.0) {
+        initials_entry.0 = Some(String::new());
+        initials_entry_value("", score.0)
+    } else {
+        initials_entry.0 = None;
+        game_over_value(&high_scores, score.0)
+    };
+
+    commands.spawn((menu_text(&value, GameState::GameOver), GameOverText));
+}
+
+/// Starts a new run or opens the settings from the main menu
+fn start_game_listener(
+    key: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut new_game_event_tx: EventWriter<NewGameEvent>,
+) {
+    if key.just_pressed(KeyCode::Enter) {
+        new_game_event_tx.send(NewGameEvent);
+        next_state.set(GameState::Playing);
+    } else if key.just_pressed(SETTINGS_KEY) {
+        next_state.set(GameState::Settings);
+    }
+}
+
+/// Cycles through the fire modes from the main menu
+fn change_fire_mode_listener(
+    key: Res<ButtonInput<KeyCode>>,
+    mut fire_mode: ResMut<FireMode>,
+    mut fire_mode_text_query: Query<&mut Text, With<FireModeText>>,
+) {
+    if key.just_pressed(CHANGE_FIRE_MODE_KEY) {
+        *fire_mode = fire_mode.next();
+
+        if let Ok(mut fire_mode_text) = fire_mode_text_query.get_single_mut() {
+            fire_mode_text.sections = vec![fire_mode_section(*fire_mode)];
+        }
+    }
+}
+
+/// Throws away the paused run and starts a fresh one
+fn restart_game_listener(
+    key: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut new_game_event_tx: EventWriter<NewGameEvent>,
+) {
+    if key.just_pressed(KeyCode::KeyR) {
+        new_game_event_tx.send(NewGameEvent);
+        next_state.set(GameState::Playing);
+    }
+}
+
+/// Types the player initials and saves the high score once they are confirmed
+fn enter_initials_listener(
+    mut keyboard_input_event_rx: EventReader<KeyboardInput>,
+    score: Res<Score>,
+    mut initials_entry: ResMut<InitialsEntry>,
+    mut high_scores: ResMut<HighScores>,
+    mut game_over_text_query: Query<&mut Text, With<GameOverText>>,
+) {
+    let Some(initials) = initials_entry.0.as_mut() else {
+        return;
+    };
+    let mut is_confirmed = false;
+
+    for event in keyboard_input_event_rx.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Character(characters) => {
+                *initials = sanitize_initials(&format!("{initials}{characters}"));
+            }
+            Key::Backspace => {
+                initials.pop();
+            }
+            Key::Enter if !initials.is_empty() => {
+                is_confirmed = true;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let value = if is_confirmed {
+        high_scores.insert(initials, score.0);
+        if let Err(error) = high_scores.save() {
+            warn!("Could not save high scores: {error}");
+        }
+        initials_entry.0 = None;
+
+        game_over_value(&high_scores, score.0)
+    } else {
+        initials_entry_value(initials, score.0)
+    };
+
+    if let Ok(mut game_over_text) = game_over_text_query.get_single_mut() {
+        game_over_text.sections[0].value = value;
+    }
+}
+
+/// Starts another run or goes back to the main menu once the game over screen is dismissed
+fn game_over_listener(
+    key: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut new_game_event_tx: EventWriter<NewGameEvent>,
+) {
+    if key.just_pressed(KeyCode::Enter) {
+        new_game_event_tx.send(NewGameEvent);
+        next_state.set(GameState::Playing);
+    } else if key.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+}
@@ -1,14 +1,32 @@
 use bevy::app::Plugin;
+use buffs::BuffsHudPlugin;
+use combo::ComboPlugin;
 use hearts::HeartsPlugin;
+use menu::MenuPlugin;
 use score::ScorePlugin;
+use settings::SettingsPlugin;
+use wave::WaveCounterPlugin;
 
+pub mod buffs;
+pub mod combo;
 pub mod hearts;
+pub mod menu;
 pub mod score;
+pub mod settings;
+pub mod wave;
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_plugins((ScorePlugin, HeartsPlugin));
+        app.add_plugins((
+            MenuPlugin,
+            SettingsPlugin,
+            ScorePlugin,
+            HeartsPlugin,
+            WaveCounterPlugin,
+            BuffsHudPlugin,
+            ComboPlugin,
+        ));
     }
 }
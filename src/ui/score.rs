@@ -1,77 +1,133 @@
 use bevy::{
-    app::{Plugin, Startup, Update},
+    app::{Plugin, PreUpdate, Update},
     math::vec3,
-    prelude::{Bundle, Commands, Component, EventReader, Query, Transform, With},
-    text::{Text, Text2dBundle, TextSection},
+    prelude::{
+        in_state, resource_changed, Bundle, Commands, Component, Condition, EventReader,
+        IntoSystemConfigs, Query, Res, ResMut, Resource, StateScoped, Transform, With,
+    },
+    sprite::Anchor,
+    text::{Text, Text2dBundle, TextSection, TextStyle},
 };
 
 use crate::{
-    default_config::{WINDOW_X_LIMIT, WINDOW_Y_LIMIT, WINDOW_Y_PADDING},
-    enemy::EnemyDiedEvent,
+    default_config::{HUD_X_LIMIT, WINDOW_Y_LIMIT, WINDOW_Y_PADDING},
+    enemy::EnemyEscapedEvent,
+    game_state::{despawn_all_system, GameState, InGame, NewGameSet},
+    high_scores::HighScores,
 };
 
+/// Small enough for long scores to stay clear of the wave counter in the middle.
+const SCORE_FONT_SIZE: f32 = 20.0;
+const BEST_FONT_SIZE: f32 = 16.0;
+
 pub struct ScorePlugin;
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(Startup, create_score_system)
-            .add_systems(Update, increase_score_listener);
+        app.init_resource::<Score>()
+            .add_systems(
+                PreUpdate,
+                (
+                    reset_score_system,
+                    (despawn_all_system::<ScoreText>, create_score_system).chain(),
+                )
+                    .in_set(NewGameSet),
+            )
+            .add_systems(
+                Update,
+                (
+                    escaped_enemies_penalty_listener.run_if(in_state(GameState::Playing)),
+                    update_score_text_system
+                        .run_if(resource_changed::<Score>.or_else(resource_changed::<HighScores>)),
+                )
+                    .chain(),
+            );
     }
 }
 
-#[derive(Component)]
+/// Score of the current run, or of the last one once it is over.
+///
+/// Kills and bonuses are scored by the `ComboPlugin`.
+#[derive(Resource, Default)]
 pub struct Score(pub u32);
 
+/// Tags the HUD text showing the `Score` next to the best one.
+#[derive(Component)]
+struct ScoreText;
+
 #[derive(Bundle)]
-struct ScoreBundle {
+struct ScoreTextBundle {
     text: Text2dBundle,
-    score: Score,
+    score_text: ScoreText,
 }
 
-impl ScoreBundle {
+impl ScoreTextBundle {
     fn new() -> Self {
-        let score_counter = Score(0);
         let score_text = Text2dBundle {
-            text: Text {
-                sections: vec![TextSection {
-                    value: format!("SCORE: {}", score_counter.0),
-                    ..Default::default()
-                }],
-                ..Default::default()
-            },
+            // Grows rightwards as the score gets longer, aligned with the buffs below
+            text_anchor: Anchor::TopLeft,
             transform: Transform {
-                translation: vec3(-WINDOW_X_LIMIT, WINDOW_Y_LIMIT - WINDOW_Y_PADDING, 0.0),
+                translation: vec3(
+                    -HUD_X_LIMIT,
+                    WINDOW_Y_LIMIT - WINDOW_Y_PADDING + SCORE_FONT_SIZE / 2.0,
+                    0.0,
+                ),
                 ..Default::default()
             },
             ..Default::default()
         };
 
-        ScoreBundle {
+        ScoreTextBundle {
             text: score_text,
-            score: score_counter,
+            score_text: ScoreText,
         }
     }
 }
 
+fn reset_score_system(mut score: ResMut<Score>) {
+    score.0 = 0;
+}
+
 /// Spawns score counter in the screen
 fn create_score_system(mut commands: Commands) {
-    commands.spawn(ScoreBundle::new());
+    commands.spawn((ScoreTextBundle::new(), StateScoped(InGame)));
 }
 
-/// Listens for enemy deads and increases the score based on the defeated enemy kind
-fn increase_score_listener(
-    mut enemy_died_event_rx: EventReader<EnemyDiedEvent>,
-    mut score_query: Query<(&mut Score, &mut Text), With<Score>>,
+/// Listens for enemies getting past the ship and takes away the score they were worth
+fn escaped_enemies_penalty_listener(
+    mut enemy_escaped_event_rx: EventReader<EnemyEscapedEvent>,
+    mut score: ResMut<Score>,
 ) {
-    for event in enemy_died_event_rx.read() {
-        if let Ok(score) = score_query.get_single_mut() {
-            let (mut score_counter, mut score_text) = score;
+    for event in enemy_escaped_event_rx.read() {
+        score.0 = score.0.saturating_sub(event.0.score());
+    }
+}
 
-            score_counter.0 += event.0.score();
-            score_text.sections = vec![TextSection {
-                value: format!("SCORE: {}", score_counter.0),
-                ..Default::default()
-            }];
-        }
+/// Shows the score of the run and, in smaller print under it, the best score ever, which the
+/// run may be beating
+fn update_score_text_system(
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    mut score_text_query: Query<&mut Text, With<ScoreText>>,
+) {
+    let best = high_scores.best().unwrap_or_default().max(score.0);
+
+    for mut score_text in &mut score_text_query {
+        score_text.sections = vec![
+            TextSection::new(
+                format!("SCORE: {}\n", score.0),
+                TextStyle {
+                    font_size: SCORE_FONT_SIZE,
+                    ..Default::default()
+                },
+            ),
+            TextSection::new(
+                format!("BEST: {best}"),
+                TextStyle {
+                    font_size: BEST_FONT_SIZE,
+                    ..Default::default()
+                },
+            ),
+        ];
     }
 }
@@ -0,0 +1,208 @@
+use bevy::{
+    app::{Plugin, Update},
+    input::ButtonInput,
+    log::warn,
+    prelude::{
+        in_state, Axis, Commands, Component, GamepadAxis, GamepadAxisType, GamepadButton, Gamepads,
+        IntoSystemConfigs, KeyCode, NextState, OnEnter, Query, Res, ResMut, Resource, StateScoped,
+        With,
+    },
+    text::{JustifyText, Text, Text2dBundle, TextSection, TextStyle},
+};
+
+use crate::{
+    game_state::GameState,
+    input::{Action, ActionBindings, AnalogResponse, Binding, AXIS_PRESS_THRESHOLD},
+};
+
+const SETTINGS_FONT_SIZE: f32 = 16.0;
+/// Rows of the settings menu: one per action, then the deadzone and the response curve.
+const SETTINGS_ROWS: usize = Action::ALL.len() + 2;
+const DEADZONE_ROW: usize = Action::ALL.len();
+const CURVE_ROW: usize = Action::ALL.len() + 1;
+const DEADZONE_STEP: f32 = 0.05;
+const CURVE_STEP: f32 = 0.25;
+/// Axes which can be bound by pushing them while rebinding an action.
+const BINDABLE_AXES: [GamepadAxisType; 4] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+];
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<SettingsMenu>()
+            .add_systems(OnEnter(GameState::Settings), create_settings_menu_system)
+            .add_systems(
+                Update,
+                (settings_menu_listener, update_settings_menu_system)
+                    .chain()
+                    .run_if(in_state(GameState::Settings)),
+            );
+    }
+}
+
+/// Cursor of the settings menu.
+#[derive(Resource, Default)]
+struct SettingsMenu {
+    /// Index of the highlighted row, which is an index in `Action::ALL` for action rows.
+    selected: usize,
+    /// Whether the next pressed input will be bound to the highlighted action.
+    is_rebinding: bool,
+}
+
+/// Tags the text of the settings menu.
+#[derive(Component)]
+struct SettingsText;
+
+/// Renders the settings menu
+fn create_settings_menu_system(mut commands: Commands, mut menu: ResMut<SettingsMenu>) {
+    *menu = SettingsMenu::default();
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::default().with_justify(JustifyText::Center),
+            ..Default::default()
+        },
+        SettingsText,
+        StateScoped(GameState::Settings),
+    ));
+}
+
+/// Moves through the actions and rebinds them, saving every change
+///
+/// Menus are driven by fixed keys so they can never be locked out by a bad binding.
+#[allow(clippy::too_many_arguments)]
+fn settings_menu_listener(
+    key: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut menu: ResMut<SettingsMenu>,
+    mut bindings: ResMut<ActionBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if menu.is_rebinding {
+        if key.just_pressed(KeyCode::Escape) {
+            menu.is_rebinding = false;
+            return;
+        }
+
+        let pressed_axis = gamepads.iter().find_map(|gamepad| {
+            BINDABLE_AXES.into_iter().find_map(|axis| {
+                let value = gamepad_axes.get(GamepadAxis::new(gamepad, axis))?;
+
+                (value.abs() >= AXIS_PRESS_THRESHOLD).then_some(Binding::GamepadAxis {
+                    axis,
+                    positive: value > 0.0,
+                })
+            })
+        });
+        let binding = key
+            .get_just_pressed()
+            .next()
+            .map(|key_code| Binding::Key(*key_code))
+            .or_else(|| {
+                gamepad_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Binding::GamepadButton(button.button_type))
+            })
+            .or(pressed_axis);
+
+        if let Some(binding) = binding {
+            bindings.rebind(Action::ALL[menu.selected], binding);
+            save_bindings(&bindings);
+            menu.is_rebinding = false;
+        }
+    } else if key.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + SETTINGS_ROWS - 1) % SETTINGS_ROWS;
+    } else if key.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % SETTINGS_ROWS;
+    } else if key.just_pressed(KeyCode::ArrowLeft) || key.just_pressed(KeyCode::ArrowRight) {
+        let sign = if key.just_pressed(KeyCode::ArrowLeft) {
+            -1.0
+        } else {
+            1.0
+        };
+        let analog = &mut bindings.analog;
+
+        match menu.selected {
+            DEADZONE_ROW => {
+                analog.deadzone = (analog.deadzone + sign * DEADZONE_STEP)
+                    .clamp(0.0, AnalogResponse::MAX_DEADZONE);
+            }
+            CURVE_ROW => {
+                analog.curve = (analog.curve + sign * CURVE_STEP)
+                    .clamp(AnalogResponse::MIN_CURVE, AnalogResponse::MAX_CURVE);
+            }
+            _ => return,
+        }
+        save_bindings(&bindings);
+    } else if key.just_pressed(KeyCode::Enter) && menu.selected < Action::ALL.len() {
+        menu.is_rebinding = true;
+    } else if key.just_pressed(KeyCode::Backspace) {
+        *bindings = ActionBindings::default();
+        save_bindings(&bindings);
+    } else if key.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn save_bindings(bindings: &ActionBindings) {
+    if let Err(error) = bindings.save() {
+        warn!("Could not save input bindings: {error}");
+    }
+}
+
+/// Lists every action with its bindings, highlighting the selected one
+fn update_settings_menu_system(
+    menu: Res<SettingsMenu>,
+    bindings: Res<ActionBindings>,
+    mut settings_text_query: Query<&mut Text, With<SettingsText>>,
+) {
+    let Ok(mut settings_text) = settings_text_query.get_single_mut() else {
+        return;
+    };
+
+    let mut value = String::from(
+        "SETTINGS\n\nUP/DOWN to select, ENTER to rebind, LEFT/RIGHT to adjust\n\
+         BACKSPACE for defaults, ESC to go back\n\n",
+    );
+
+    for (index, action) in Action::ALL.into_iter().enumerate() {
+        let cursor = if index == menu.selected { "> " } else { "" };
+        let bound = if index == menu.selected && menu.is_rebinding {
+            "press a key or button...".to_string()
+        } else {
+            bindings
+                .get(action)
+                .iter()
+                .map(Binding::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        value.push_str(&format!("{cursor}{}\n{bound}\n\n", action.label()));
+    }
+
+    let cursor = |row| if row == menu.selected { "> " } else { "" };
+    value.push_str(&format!(
+        "{}STICK DEADZONE: {:.2}\n{}STICK RESPONSE CURVE: {:.2}",
+        cursor(DEADZONE_ROW),
+        bindings.analog.deadzone,
+        cursor(CURVE_ROW),
+        bindings.analog.curve,
+    ));
+
+    settings_text.sections = vec![TextSection {
+        value,
+        style: TextStyle {
+            font_size: SETTINGS_FONT_SIZE,
+            ..Default::default()
+        },
+    }];
+}
@@ -0,0 +1,88 @@
+use bevy::{
+    app::{Plugin, PreUpdate, Update},
+    math::vec3,
+    prelude::{
+        in_state, Bundle, Commands, Component, EventReader, IntoSystemConfigs, Query, StateScoped,
+        Transform, With,
+    },
+    text::{Text, Text2dBundle, TextSection},
+};
+
+use crate::{
+    default_config::{WINDOW_Y_LIMIT, WINDOW_Y_PADDING},
+    enemy::wave::WaveStartedEvent,
+    game_state::{despawn_all_system, GameState, InGame, NewGameSet},
+};
+
+pub struct WaveCounterPlugin;
+
+impl Plugin for WaveCounterPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_systems(
+            PreUpdate,
+            (
+                despawn_all_system::<WaveCounter>,
+                create_wave_counter_system,
+            )
+                .chain()
+                .in_set(NewGameSet),
+        )
+        .add_systems(
+            Update,
+            update_wave_counter_listener.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Tags the HUD text showing the current wave.
+#[derive(Component)]
+pub struct WaveCounter;
+
+#[derive(Bundle)]
+struct WaveCounterBundle {
+    text: Text2dBundle,
+    wave_counter: WaveCounter,
+}
+
+impl WaveCounterBundle {
+    fn new() -> Self {
+        WaveCounterBundle {
+            text: Text2dBundle {
+                text: Text {
+                    sections: vec![wave_counter_section(0)],
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: vec3(0.0, WINDOW_Y_LIMIT - WINDOW_Y_PADDING, 0.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            wave_counter: WaveCounter,
+        }
+    }
+}
+
+fn wave_counter_section(wave_number: u32) -> TextSection {
+    TextSection {
+        value: format!("WAVE: {}", wave_number),
+        ..Default::default()
+    }
+}
+
+/// Spawns wave counter in the screen
+fn create_wave_counter_system(mut commands: Commands) {
+    commands.spawn((WaveCounterBundle::new(), StateScoped(InGame)));
+}
+
+/// Listens for started waves and shows their number
+fn update_wave_counter_listener(
+    mut wave_started_event_rx: EventReader<WaveStartedEvent>,
+    mut wave_counter_query: Query<&mut Text, With<WaveCounter>>,
+) {
+    for event in wave_started_event_rx.read() {
+        if let Ok(mut wave_counter_text) = wave_counter_query.get_single_mut() {
+            wave_counter_text.sections = vec![wave_counter_section(event.0)];
+        }
+    }
+}
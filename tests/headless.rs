@@ -0,0 +1,77 @@
+//! Plays the game without a window through `HeadlessConfigPlugins`, and checks what happened.
+
+use bevy::prelude::*;
+use rusty_space_invaders::{
+    bunker::BunkerPlugin,
+    enemy::{wave::WaveStartedEvent, Enemy},
+    headless::{run_headless, HeadlessConfigPlugins},
+    play_area::{EscapeSet, EscapedEvent},
+    player::shot::Shot,
+    SpaceInvadersPlugin,
+};
+
+/// Long enough for the first wave to start and for missed shots to fly off the screen.
+const TICKS: u32 = 900;
+
+/// What the headless run went through, recorded by `record_system`.
+#[derive(Resource, Default)]
+struct Record {
+    waves_started: Vec<u32>,
+    most_enemies: usize,
+    escaped_shots: Vec<Entity>,
+}
+
+fn record_system(
+    mut wave_started_event_rx: EventReader<WaveStartedEvent>,
+    mut escaped_event_rx: EventReader<EscapedEvent>,
+    enemies_query: Query<(), With<Enemy>>,
+    shots_query: Query<(), With<Shot>>,
+    mut record: ResMut<Record>,
+) {
+    for WaveStartedEvent(wave_number) in wave_started_event_rx.read() {
+        record.waves_started.push(*wave_number);
+    }
+
+    for EscapedEvent(entity, _) in escaped_event_rx.read() {
+        if shots_query.contains(*entity) {
+            record.escaped_shots.push(*entity);
+        }
+    }
+
+    record.most_enemies = record.most_enemies.max(enemies_query.iter().count());
+}
+
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        HeadlessConfigPlugins,
+        // Bunkers would stop the shots before they reach the top of the screen
+        SpaceInvadersPlugin.build().disable::<BunkerPlugin>(),
+    ))
+    .init_resource::<Record>()
+    .add_systems(FixedPostUpdate, record_system.after(EscapeSet));
+
+    app
+}
+
+#[test]
+fn headless_run_plays_the_first_wave() {
+    let mut app = headless_app();
+
+    run_headless(&mut app, TICKS);
+
+    let record = app.world().resource::<Record>();
+    assert_eq!(record.waves_started.first(), Some(&1));
+    assert!(record.most_enemies > 0, "no enemy spawned");
+    assert!(
+        !record.escaped_shots.is_empty(),
+        "no shot left the play area"
+    );
+
+    for shot_entity in &record.escaped_shots {
+        assert!(
+            app.world().get_entity(*shot_entity).is_none(),
+            "shot {shot_entity} left the play area but was not despawned"
+        );
+    }
+}